@@ -0,0 +1,140 @@
+//! Pincel usado para inserir partículas com o mouse.
+//!
+//! O pincel cobre as células em volta do cursor, de acordo com o seu raio e a sua [forma](BrushShape). A cada passo em que o botão do
+//! mouse está pressionado, uma partícula é inserida em cada célula livre coberta pelo pincel; as células ocupadas são mantidas.
+//! ```text
+//! raio 2, círculo     raio 2, quadrado
+//!   . # # # .           # # # # #
+//!   # # # # #           # # # # #
+//!   # # # # #           # # # # #
+//!   # # # # #           # # # # #
+//!   . # # # .           # # # # #
+//! ```
+//! No spray, cada célula do círculo é coberta apenas com a chance `density`, espalhando as partículas.
+use crate::implparticles::position_to_index;
+use rand::Rng;
+
+/// Maior raio do pincel, em células
+pub const MAX_BRUSH_RADIUS: u32 = 32;
+/// Cor do contorno do pincel desenhado sobre o frame
+pub const BRUSH_OUTLINE_COLOR: [u8; 4] = [0xff, 0xff, 0xff, 0xff];
+
+/// Forma do pincel
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BrushShape {
+    /// Todas as células a até `radius` células do cursor
+    Circle,
+    /// Todas as células do quadrado de lado `2 * radius + 1` centrado no cursor
+    Square,
+    /// Células do círculo escolhidas ao acaso, cada uma com a chance `density`
+    Spray,
+}
+
+/// Pincel de inserção de partículas
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Brush {
+    /// Raio do pincel, em células. Com raio 0 o pincel cobre apenas a célula do cursor
+    pub radius: u32,
+    /// Forma do pincel
+    pub shape: BrushShape,
+    /// Chance, entre 0 e 1, de cada célula ser coberta pelo [spray](BrushShape::Spray)
+    pub density: f32,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Brush {
+            radius: 0,
+            shape: BrushShape::Circle,
+            density: 0.1,
+        }
+    }
+}
+
+impl Brush {
+    /// Aumenta (`delta` positivo) ou diminui o raio do pincel, entre 0 e [MAX_BRUSH_RADIUS]
+    pub fn resize(&mut self, delta: i32) {
+        self.radius = (self.radius as i64 + delta as i64).clamp(0, MAX_BRUSH_RADIUS as i64) as u32;
+    }
+
+    /// Troca a forma do pincel pela seguinte: círculo, quadrado, spray
+    pub fn next_shape(&mut self) {
+        self.shape = match self.shape {
+            BrushShape::Circle => BrushShape::Square,
+            BrushShape::Square => BrushShape::Spray,
+            BrushShape::Spray => BrushShape::Circle,
+        };
+    }
+
+    /// Altera a densidade do spray, mantendo-a entre 0.01 e 1
+    pub fn set_density(&mut self, density: f32) {
+        self.density = density.clamp(0.01, 1.0);
+    }
+
+    /// Verifica se o deslocamento (dx, dy) em relação ao cursor está dentro da área do pincel, desconsiderando a densidade do spray
+    fn covers(&self, dx: i64, dy: i64) -> bool {
+        let radius = self.radius as i64;
+        match self.shape {
+            BrushShape::Square => dx.abs() <= radius && dy.abs() <= radius,
+            BrushShape::Circle | BrushShape::Spray => dx * dx + dy * dy <= radius * radius + radius,
+        }
+    }
+
+    /// Células de um mundo `width` x `height` dentro da área do pincel com o cursor na célula x,y, junto do seu deslocamento (dx, dy)
+    /// em relação ao cursor
+    fn area(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> impl Iterator<Item = ((i64, i64), (u32, u32))> + '_ {
+        let radius = self.radius as i64;
+        (-radius..=radius)
+            .flat_map(move |dy| (-radius..=radius).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| self.covers(dx, dy))
+            .filter_map(move |(dx, dy)| {
+                let (cx, cy) = (x as i64 + dx, y as i64 + dy);
+                let inside = cx >= 0 && cy >= 0 && cx < width as i64 && cy < height as i64;
+                inside.then_some(((dx, dy), (cx as u32, cy as u32)))
+            })
+    }
+
+    /// Células de um mundo `width` x `height` cobertas pelo pincel com o cursor na célula x,y. No spray, as células são sorteadas com
+    /// `rng`, que não precisa ser o gerador da simulação
+    ///
+    /// ```
+    /// use sandbox::brush::{Brush, BrushShape};
+    ///
+    /// let brush = Brush { radius: 1, shape: BrushShape::Square, density: 1.0 };
+    /// let cells = brush.cells(0, 0, 10, 10, &mut rand::thread_rng());
+    /// assert_eq!(cells, vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+    /// ```
+    pub fn cells(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        rng: &mut impl Rng,
+    ) -> Vec<(u32, u32)> {
+        self.area(x, y, width, height)
+            .filter(|_| self.shape != BrushShape::Spray || rng.gen::<f32>() < self.density)
+            .map(|(_, cell)| cell)
+            .collect()
+    }
+
+    /// Desenha o contorno do pincel com o cursor na célula x,y sobre um frame rgba de `width` x `height` píxeis: as células cobertas
+    /// pelo pincel que têm algum vizinho fora dele
+    pub fn draw_outline(&self, frame: &mut [u8], x: u32, y: u32, width: u32, height: u32) {
+        for ((dx, dy), (cx, cy)) in self.area(x, y, width, height) {
+            let border = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .any(|&(ox, oy)| !self.covers(dx + ox, dy + oy));
+            if border {
+                let index = position_to_index(cx, cy, width);
+                frame[index..index + 4].copy_from_slice(&BRUSH_OUTLINE_COLOR);
+            }
+        }
+    }
+}
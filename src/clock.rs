@@ -0,0 +1,162 @@
+//! Relógio de passo fixo da simulação.
+//!
+//! A simulação avança sempre em passos de mesma duração (`1 / ticks_per_second`), independentemente da taxa de quadros da
+//! renderização ou da frequência de eventos da janela. O tempo real decorrido é acumulado e convertido em um número inteiro de passos:
+//! ```text
+//! acumulado += decorrido * velocidade
+//! enquanto acumulado >= passo { acumulado -= passo; world.step(rng) }
+//! ```
+use std::time::Duration;
+
+/// Ticks por segundo padrão da simulação
+pub const DEFAULT_TICKS_PER_SECOND: u32 = 60;
+/// Limite de passos executados por chamada a [FixedTimestep::advance], evitando que uma pausa longa (ou um passo lento)
+/// faça a simulação tentar recuperar um atraso cada vez maior
+pub const MAX_TICKS_PER_ADVANCE: u32 = 8;
+/// Menor multiplicador de velocidade aceito
+pub const MIN_SPEED: f64 = 0.125;
+/// Maior multiplicador de velocidade aceito
+pub const MAX_SPEED: f64 = 8.0;
+
+/// Acumulador de tempo que decide quantos passos da simulação devem ser executados
+#[derive(Clone, Debug)]
+pub struct FixedTimestep {
+    tick: Duration,
+    accumulator: Duration,
+    speed: f64,
+    paused: bool,
+    pending_steps: u32,
+}
+
+impl FixedTimestep {
+    /// Cria um relógio com `ticks_per_second` passos por segundo, na velocidade 1x
+    pub fn new(ticks_per_second: u32) -> FixedTimestep {
+        assert!(
+            ticks_per_second > 0,
+            "a simulação precisa de ao menos um tick por segundo"
+        );
+        FixedTimestep {
+            tick: Duration::from_secs(1) / ticks_per_second,
+            accumulator: Duration::ZERO,
+            speed: 1.0,
+            paused: false,
+            pending_steps: 0,
+        }
+    }
+
+    /// Duração de um passo da simulação
+    pub fn tick(&self) -> Duration {
+        self.tick
+    }
+
+    /// Acumula `elapsed` de tempo real e retorna quantos passos devem ser executados agora.
+    ///
+    /// Pausado, o tempo não é acumulado e apenas os passos pedidos com [FixedTimestep::single_step] são retornados.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        if self.paused {
+            let steps = self.pending_steps;
+            self.pending_steps = 0;
+            return steps;
+        }
+
+        self.accumulator += elapsed.mul_f64(self.speed);
+        let mut steps = 0;
+        while self.accumulator >= self.tick {
+            self.accumulator -= self.tick;
+            steps += 1;
+            if steps == MAX_TICKS_PER_ADVANCE {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+        }
+        steps
+    }
+
+    /// Verifica se a simulação está pausada
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pausa ou retoma a simulação, descartando o tempo acumulado
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.accumulator = Duration::ZERO;
+        self.pending_steps = 0;
+    }
+
+    /// Com a simulação pausada, agenda a execução de exatamente um passo na próxima chamada a [FixedTimestep::advance]
+    pub fn single_step(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    /// Multiplicador de velocidade atual
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Altera o multiplicador de velocidade, limitado entre [MIN_SPEED] e [MAX_SPEED]
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TICK: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn advance_accumulates_partial_ticks() {
+        let mut clock = FixedTimestep::new(10);
+        assert_eq!(clock.tick(), TICK);
+        assert_eq!(clock.advance(TICK / 2), 0);
+        assert_eq!(clock.advance(TICK / 2), 1);
+        assert_eq!(clock.advance(TICK * 2 + TICK / 2), 2);
+        assert_eq!(clock.advance(TICK / 2), 1);
+    }
+
+    #[test]
+    fn advance_is_capped() {
+        let mut clock = FixedTimestep::new(10);
+        assert_eq!(clock.advance(TICK * 100), MAX_TICKS_PER_ADVANCE);
+        assert_eq!(clock.advance(Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn pause_discards_time() {
+        let mut clock = FixedTimestep::new(10);
+        assert_eq!(clock.advance(TICK / 2), 0);
+        clock.toggle_pause();
+        assert!(clock.is_paused());
+        assert_eq!(clock.advance(TICK * 5), 0);
+        clock.toggle_pause();
+        assert_eq!(clock.advance(TICK / 2), 0);
+        assert_eq!(clock.advance(TICK / 2), 1);
+    }
+
+    #[test]
+    fn single_step_only_while_paused() {
+        let mut clock = FixedTimestep::new(10);
+        clock.single_step();
+        assert_eq!(clock.advance(Duration::ZERO), 0);
+        clock.toggle_pause();
+        clock.single_step();
+        clock.single_step();
+        assert_eq!(clock.advance(TICK * 5), 2);
+        assert_eq!(clock.advance(Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn speed_scales_time_and_is_clamped() {
+        let mut clock = FixedTimestep::new(10);
+        clock.set_speed(2.0);
+        assert_eq!(clock.advance(TICK), 2);
+        clock.set_speed(100.0);
+        assert_eq!(clock.speed(), MAX_SPEED);
+        clock.set_speed(0.0);
+        assert_eq!(clock.speed(), MIN_SPEED);
+    }
+}
@@ -0,0 +1,107 @@
+//! Configuração da execução, lida da linha de comando.
+//!
+//! ```text
+//! sandbox --width 1920 --height 1080 --scale 1 --seed 42 --tps 120 --materials meus_materiais.toml
+//! ```
+//!
+//! Opções não informadas assumem os valores padrão de [Config::default].
+use crate::clock::DEFAULT_TICKS_PER_SECOND;
+use crate::world::{frame_len, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+
+/// Texto de ajuda com as opções aceitas
+pub const USAGE: &str =
+    "Uso: sandbox [--width N] [--height N] [--scale N] [--seed N] [--tps N] [--materials ARQUIVO]";
+
+/// Opções de execução da simulação
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Largura do mundo, em células
+    pub width: u32,
+    /// Altura do mundo, em células
+    pub height: u32,
+    /// Quantos píxeis da janela cada célula ocupa inicialmente
+    pub scale: u32,
+    /// Semente do gerador aleatório da simulação. Sem semente, uma é sorteada e exibida para que a execução possa ser reproduzida
+    pub seed: Option<u64>,
+    /// Passos da simulação por segundo, independente da taxa de quadros
+    pub ticks_per_second: u32,
+    /// Arquivo de materiais aplicado por cima dos materiais padrão, ver [crate::material]
+    pub materials: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            scale: 2,
+            seed: None,
+            ticks_per_second: DEFAULT_TICKS_PER_SECOND,
+            materials: None,
+        }
+    }
+}
+
+impl Config {
+    /// Lê as opções a partir dos argumentos da linha de comando, sem o nome do programa. Mundos cujo [frame](frame_len) tem mais que
+    /// `u32::MAX` bytes são recusados, já que o tamanho do frame da janela é calculado em `u32`
+    ///
+    /// ```
+    /// use sandbox::config::Config;
+    ///
+    /// let args = ["--width", "32", "--height", "16"].iter().map(|arg| arg.to_string());
+    /// let config = Config::from_args(args).unwrap();
+    /// assert_eq!((config.width, config.height), (32, 16));
+    ///
+    /// let args = ["--width", "70000", "--height", "70000"].iter().map(|arg| arg.to_string());
+    /// assert!(Config::from_args(args).is_err());
+    /// ```
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--width" => config.width = parse_positive(&arg, &mut args)?,
+                "--height" => config.height = parse_positive(&arg, &mut args)?,
+                "--scale" => config.scale = parse_positive(&arg, &mut args)?,
+                "--seed" => config.seed = Some(parse_seed(&arg, &mut args)?),
+                "--tps" => config.ticks_per_second = parse_positive(&arg, &mut args)?,
+                "--materials" => config.materials = Some(next_value(&arg, &mut args)?),
+                _ => return Err(format!("opção desconhecida: {}", arg)),
+            }
+        }
+        if frame_len(config.width, config.height).is_none_or(|len| len > u32::MAX as usize) {
+            return Err(format!(
+                "o mundo {}x{} é grande demais",
+                config.width, config.height
+            ));
+        }
+        Ok(config)
+    }
+}
+
+/// Lê o valor seguinte à opção `arg`
+fn next_value(arg: &str, args: &mut impl Iterator<Item = String>) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("{} precisa de um valor", arg))
+}
+
+/// Lê o valor da opção `arg` como um inteiro positivo
+fn parse_positive(arg: &str, args: &mut impl Iterator<Item = String>) -> Result<u32, String> {
+    let value = next_value(arg, args)?;
+    match value.parse::<u32>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!(
+            "{} deve ser um inteiro positivo, recebido {}",
+            arg, value
+        )),
+    }
+}
+
+/// Lê o valor da opção `arg` como uma semente de 64 bits
+fn parse_seed(arg: &str, args: &mut impl Iterator<Item = String>) -> Result<u64, String> {
+    let value = next_value(arg, args)?;
+    value
+        .parse::<u64>()
+        .map_err(|_| format!("{} deve ser um inteiro sem sinal, recebido {}", arg, value))
+}
@@ -1,13 +1,25 @@
-//! Implementação das traits [base](BaseParticle) para cada partícula e definição de [position_to_index]
+//! Implementação da trait [base](BaseParticle) da partícula e definição de [position_to_index]
 //!
-//! Todas as partículas se movem 1 píxel por loop lógico, não sendo aplicada aceleração ou outros conceitos físicos, apenas um deslocamento unitário
+//! O comportamento de cada partícula é escolhido pelo seu material em [BaseParticle::move_particle]. Os materiais com comportamento
+//! próprio (fogo, lava, ácido...) têm uma função própria; os demais, inclusive os [personalizados](ParticleNum::Custom), se movem
+//! de acordo com a sua [classe de comportamento](Behaviour): pó, líquido, gás ou sólido. Qualquer material com
+//! [força de explosão](crate::material::Material::explosion_strength), como a pólvora, explode ao ser tocado pelo fogo ou pela eletricidade.
 //!
-use crate::*;
+//! Os pós e os líquidos possuem velocidade (vx, vy), acelerada pela [GRAVITY] até a
+//! [TERMINAL_VELOCITY]. Um deslocamento de várias células é resolvido percorrendo o caminho com [World::trace], de forma que uma areia rápida
+//! não atravessa o ferro. Ao serem bloqueadas, sua velocidade é zerada. As demais partículas se movem no máximo 1 píxel por loop lógico.
+//!
+//! As colisões são decididas consultando o [World]: uma célula está livre quando [World::is_free] retorna verdadeiro, isto é, está vazia
+//! ou é um [vazio](ParticleNum::Void), que apaga a partícula que entra nele. As partículas que caem também podem entrar em células
+//! ocupadas por fluidos menos densos ([World::can_enter]), trocando de lugar com eles.
+//!
+//! Toda aleatoriedade (direção da água e da partícula agitada) vem do [SimRng] recebido, nunca de `rand::thread_rng()`,
+//! para que a simulação seja reproduzível a partir de uma semente.
+//!
+use crate::material::Behaviour;
+use crate::particle::*;
+use crate::world::{Command, SimRng, World};
 use rand::Rng;
-///Largura Tela
-pub static WIDTH: u32 = 200;
-///Altura Tela
-pub static HEIGHT: u32 = 150;
 
 /// Mapeia uma posição na tela a um índice de píxel no frame da tela
 ///
@@ -23,230 +35,420 @@ pub static HEIGHT: u32 = 150;
 /// ```
 /// Sendo assim, a função calcula a "linha" a partir de y, e a "coluna" para o píxel a partir de x, retornando o índice da componente r, na qual os próximos 3
 /// serão os outros componentes.
+/// ```ignore
+/// (y as usize * width as usize + x as usize) * 4
 /// ```
-/// return ((y * WIDTH + x) * 4) as usize;
-/// ```
-pub fn position_to_index(x: u32, y: u32) -> usize {
-    return ((y * WIDTH + x) * 4) as usize;
+/// As contas são feitas em `usize`, de forma que mundos grandes não estouram o `u32`; o tamanho do frame é validado em [frame_len](crate::world::frame_len).
+pub fn position_to_index(x: u32, y: u32, width: u32) -> usize {
+    (y as usize * width as usize + x as usize) * 4
 }
-//[][][][][] WIDTH*Heigh /30000  0   1    2    3      --- 400
-//                               400 401 402 403          400
-//                               800 801 803 803 -
+/// Aceleração da gravidade, em células por tick²
+pub const GRAVITY: f32 = 0.25;
+/// Velocidade máxima de queda, em células por tick
+pub const TERMINAL_VELOCITY: f32 = 4.0;
+/// Fração da velocidade horizontal mantida a cada tick
+pub const AIR_DRAG: f32 = 0.9;
 
-impl BaseParticle for Particle {
-    fn move_particle(&mut self, frame: &mut [u8]) {
-        if self.colision(frame) {
-            return;
-        }
-        self.y += 1
+/// Aplica a gravidade à velocidade e desloca a partícula ao longo do caminho (vx, vy), parando antes do primeiro obstáculo.
+///
+/// Caso o caminho seja interrompido, a velocidade é zerada e `false` é retornado.
+fn fall(
+    kind: ParticleNum,
+    x: &mut u32,
+    y: &mut u32,
+    vx: &mut f32,
+    vy: &mut f32,
+    world: &World,
+) -> bool {
+    *vy = (*vy + GRAVITY).min(TERMINAL_VELOCITY);
+    *vx *= AIR_DRAG;
+    let dx = vx.round() as i32;
+    let dy = vy.ceil() as i32;
+    let target = world.trace(kind, (*x, *y), dx, dy);
+    let reached =
+        target.0 as i64 == *x as i64 + dx as i64 && target.1 as i64 == *y as i64 + dy as i64;
+    (*x, *y) = target;
+    if !reached {
+        *vx = 0.0;
+        *vy = 0.0;
     }
+    reached
+}
 
-    fn colision(&self, frame: &mut [u8]) -> bool {
-        if self.y + 1 >= HEIGHT {
-            return true;
-        }
+//[][][][][] width*height /30000  0   1    2    3      --- 400
+//                               400 401 402 403          400
+//                               800 801 803 803 -
 
-        let index: usize = position_to_index(self.x, self.y + 1);
-        if (frame[index + 2]) == 150 {
-            return false;
-        } else {
-            return true;
-        }
+/// Movimento de pó: cai e, quando bloqueado, escorrega para as diagonais de baixo, formando pirâmides de apoio.
+/// Uma partícula arremessada para cima (`vy` negativo) segue a sua trajetória até voltar a cair
+fn move_powder(
+    kind: ParticleNum,
+    x: &mut u32,
+    y: &mut u32,
+    vx: &mut f32,
+    vy: &mut f32,
+    world: &World,
+) {
+    if *vy < 0.0 {
+        fall(kind, x, y, vx, vy, world);
+        return;
+    }
+    if *y + 1 >= world.height() {
+        *vx = 0.0;
+        *vy = 0.0;
+        return;
+    }
+    if world.can_enter(kind, *x, *y + 1) {
+        fall(kind, x, y, vx, vy, world);
+        return;
+    }
+    *vx = 0.0;
+    *vy = 0.0;
+    if *x != 0 && world.can_enter(kind, *x - 1, *y + 1) {
+        *y += 1;
+        *x -= 1;
+        return;
+    }
+    if *x != world.width() - 1 && world.can_enter(kind, *x + 1, *y + 1) {
+        *y += 1;
+        *x += 1;
     }
 }
 
-impl BaseParticle for SandParticle {
-    fn move_particle(&mut self, frame: &mut [u8]) {
-        if self.colision(frame) {
-            return;
+/// Chance, a cada passo, de uma partícula de vapor no teto se condensar
+pub const STEAM_CONDENSE_CHANCE: f32 = 0.02;
+/// Chance de um fogo sem resíduo deixar fumaça ao se apagar
+pub const FIRE_SMOKE_CHANCE: f32 = 0.5;
+/// Temperatura, em °C, a partir da qual a pólvora, e qualquer outro material explosivo, explode mesmo sem ser tocada pelo fogo
+pub const GUNPOWDER_IGNITION_POINT: f32 = 250.0;
+/// Chance, a cada passo, da lava apoiada ficar parada em vez de escorrer para os lados. Quanto maior, mais devagar ela se espalha; a
+/// queda livre não é afetada
+pub const LAVA_VISCOSITY: f32 = 0.75;
+
+/// Movimento de gás: sobe, desviando pelas diagonais de cima, e se espalha aleatoriamente para os lados quando bloqueado.
+/// Gases atravessam fluidos mais densos ([World::can_float_into]), mas são bloqueados por sólidos e pós.
+///
+/// Retorna `false` caso o gás não tenha conseguido subir, isto é, esteja no teto ou sob um obstáculo
+fn move_gas(kind: ParticleNum, x: &mut u32, y: &mut u32, world: &World, rng: &mut SimRng) -> bool {
+    let side: i64 = if rng.gen::<bool>() { 1 } else { -1 };
+    let sideways = |dx: i64| -> Option<u32> {
+        let nx = *x as i64 + dx;
+        (nx >= 0 && nx < world.width() as i64).then_some(nx as u32)
+    };
+
+    if *y > 0 {
+        if world.can_float_into(kind, *x, *y - 1) {
+            *y -= 1;
+            return true;
         }
-        let mut index: usize = position_to_index(self.x, self.y + 1);
-        if (frame[index + 2]) != 150 {
-            if self.x != 0 {
-                index = position_to_index(self.x - 1, self.y + 1);
-                if frame[index + 2] == 150 {
-                    self.y += 1;
-                    self.x -= 1;
-                    return;
-                }
-            }
-            if self.x != WIDTH - 1 {
-                index = position_to_index(self.x + 1, self.y + 1);
-                if frame[index + 2] == 150 {
-                    self.y += 1;
-                    self.x += 1;
-                    return;
+        for dx in [side, -side] {
+            if let Some(nx) = sideways(dx) {
+                if world.can_float_into(kind, nx, *y - 1) {
+                    *x = nx;
+                    *y -= 1;
+                    return true;
                 }
             }
-        } else {
-            self.y += 1;
-            return;
         }
     }
-    fn colision(&self, _frame: &mut [u8]) -> bool {
-        if self.y + 1 >= HEIGHT {
-            return true;
+    if let Some(nx) = sideways(side) {
+        if world.is_free(nx, *y) {
+            *x = nx;
         }
-        return false;
     }
+    false
 }
 
-impl BaseParticle for IronParticle {
-    fn move_particle(&mut self, frame: &mut [u8]) {
-        if self.colision(frame) {
-            return;
-        }
+/// Movimento de líquido: cai se possível e, caso contrário, se move aleatoriamente para a esquerda ou para a direita,
+/// com o objetivo de preencher todos os espaços do nível inferior.
+///
+/// O deslocamento lateral também troca de lugar com fluidos mais leves ([World::can_enter]), de forma que dois líquidos lado a lado
+/// se misturam até o mais denso afundar e o mais leve flutuar por cima. Como no [movimento de pó](move_powder), um líquido
+/// arremessado para cima segue a sua trajetória
+fn move_liquid(
+    kind: ParticleNum,
+    x: &mut u32,
+    y: &mut u32,
+    vx: &mut f32,
+    vy: &mut f32,
+    world: &World,
+    rng: &mut SimRng,
+) {
+    if *vy < 0.0 {
+        fall(kind, x, y, vx, vy, world);
+        return;
     }
-    fn colision(&self, _frame: &mut [u8]) -> bool {
-        return false;
+    if *y + 1 >= world.height() {
+        *vx = 0.0;
+        *vy = 0.0;
+        return;
+    }
+    if world.can_enter(kind, *x, *y + 1) {
+        fall(kind, x, y, vx, vy, world);
+        return;
+    }
+    *vx = 0.0;
+    *vy = 0.0;
+    let direction = rng.gen_range(0, 2);
+    if *x > 0 && direction == 0 && world.can_enter(kind, *x - 1, *y) {
+        *x -= 1;
+    }
+    if *x < world.width() - 1 && direction == 1 && world.can_enter(kind, *x + 1, *y) {
+        *x += 1;
     }
 }
 
-impl BaseParticle for WaterParticle {
-    //Move para baixo se possível
-    //Caso contrário, move aleatoriamente para esquerda ou direita
-    //Objetivo: preencher todos os espaços do nível inferior
-    fn move_particle(&mut self, frame: &mut [u8]) {
-        if self.colision(frame) {
-            return;
-        }
-        let index_down = position_to_index(self.x, self.y + 1);
-        if frame[index_down + 2] == 150 {
-            self.y += 1;
-        } else {
-            let mut new_x = self.x;
-            let mut new_y = self.y;
-            let direction = rand::thread_rng().gen_range(0, 2);
-            if self.x > 0 && direction == 0 {
-                let index_left = position_to_index(self.x - 1, self.y);
-                if frame[index_left + 2] == 150 {
-                    new_x = self.x - 1;
-                    new_y = self.y;
-                }
-            }
-            if self.x < WIDTH - 1 && direction == 1 {
-                let index_right = position_to_index(self.x + 1, self.y);
-                if frame[index_right + 2] == 150 {
-                    new_x = self.x + 1;
-                    new_y = self.y;
-                }
-            }
-            if new_x != self.x || new_y != self.y {
-                self.x = new_x;
-                self.y = new_y;
-            }
-        }
+/// Movimento da partícula agitada: tenta se mover para uma das quatro células vizinhas, escolhida ao acaso
+fn move_agitated(x: &mut u32, y: &mut u32, world: &World, rng: &mut SimRng) {
+    let direction = rng.gen_range(0, 4);
+    if direction == 0 && *x > 0 && world.is_free(*x - 1, *y) {
+        *x -= 1;
+    }
+    if direction == 1 && *x < world.width() - 1 && world.is_free(*x + 1, *y) {
+        *x += 1;
+    }
+    if direction == 2 && *y > 0 && world.is_free(*x, *y - 1) {
+        *y -= 1;
     }
+    if direction == 3 && *y < world.height() - 1 && world.is_free(*x, *y + 1) {
+        *y += 1;
+    }
+}
 
-    fn colision(&self, _: &mut [u8]) -> bool {
-        if self.y + 1 >= HEIGHT {
-            return true;
+/// Gasta `amount` passos de vida da partícula, retornando `true` caso a vida tenha chegado ao fim. Partículas sem tempo de vida
+/// nunca se esgotam
+fn wear_out(state: &mut State, amount: u8) -> bool {
+    match state {
+        State::LifeTime(life_time) | State::Burning { life_time, .. } => {
+            *life_time = life_time.saturating_sub(amount);
+            *life_time == 0
         }
-        return false;
+        _ => false,
     }
 }
 
-impl BaseParticle for AgitatedParticle {
-    fn move_particle(&mut self, frame: &mut [u8]) {
-        let direction = rand::thread_rng().gen_range(0, 4);
-        let mut new_x = self.x;
-        let mut new_y = self.y;
-        if direction == 0 && self.x > 0 {
-            let index_left = position_to_index(self.x - 1, self.y);
-            if frame[index_left + 2] == 150 {
-                new_x = self.x - 1;
-                new_y = self.y;
-            }
-        }
-        if direction == 1 && self.x < WIDTH - 1 {
-            let index_right = position_to_index(self.x + 1, self.y);
-            if frame[index_right + 2] == 150 {
-                new_x = self.x + 1;
-                new_y = self.y;
-            }
-        }
-        if direction == 2 && self.y > 0 {
-            let index_up = position_to_index(self.x, self.y - 1);
-            if frame[index_up + 2] == 150 {
-                new_x = self.x;
-                new_y = self.y - 1;
+/// Incendeia os vizinhos da célula x,y, cada um com a chance da sua [inflamabilidade](crate::material::Material::flammability)
+fn ignite_neighbours(x: u32, y: u32, world: &World, rng: &mut SimRng, commands: &mut Vec<Command>) {
+    for (nx, ny) in world.neighbours(x, y) {
+        if let Some(neighbour) = world.get(nx, ny) {
+            let material = world.material(neighbour.kind());
+            if rng.gen::<f32>() < material.flammability {
+                commands.push(Command::Replace(
+                    nx,
+                    ny,
+                    Particle::ignite(material.burns_into),
+                ));
             }
         }
-        if direction == 3 && self.y < HEIGHT - 1 {
-            let index_down = position_to_index(self.x, self.y + 1);
-            if frame[index_down + 2] == 150 {
-                new_x = self.x;
-                new_y = self.y + 1;
+    }
+}
+
+/// Fogo: incendeia os vizinhos inflamáveis, sobe tremulando e se apaga com o tempo, deixando o seu resíduo. A água o apaga pela
+/// reação "fire + water"
+fn burn(
+    part: &mut Particle,
+    x: &mut u32,
+    y: &mut u32,
+    world: &World,
+    rng: &mut SimRng,
+    commands: &mut Vec<Command>,
+) {
+    ignite_neighbours(*x, *y, world, rng, commands);
+
+    if wear_out(&mut part.state, rng.gen_range(1, 3)) {
+        let residue = match part.state {
+            State::Burning { residue, .. } => residue,
+            _ => None,
+        };
+        match residue {
+            Some(residue) => commands.push(Command::Replace(*x, *y, Particle::new(residue))),
+            None if rng.gen::<f32>() < FIRE_SMOKE_CHANCE => {
+                commands.push(Command::Replace(*x, *y, Particle::new(ParticleNum::Smoke)))
             }
+            None => commands.push(Command::Remove(*x, *y)),
         }
-        if new_x != self.x || new_y != self.y {
-            self.x = new_x;
-            self.y = new_y;
-        }
+        return;
     }
 
-    fn colision(&self, _: &mut [u8]) -> bool {
-        //Para que as partículas não grudem na borda, a colisão com a mesma é desconsiderada.
-        return false;
+    let new_x = match rng.gen_range(0, 3) {
+        0 if *x > 0 => *x - 1,
+        2 => *x + 1,
+        _ => *x,
+    };
+    if *y > 0 && world.is_free(new_x, *y - 1) {
+        *x = new_x;
+        *y -= 1;
     }
 }
 
-impl BaseParticle for ElectricityParticle {
-    fn move_particle(&mut self, frame: &mut [u8]) {
-        let direction = rand::thread_rng().gen_range(0, 4);
-        let mut new_x = self.x;
-        let mut new_y = self.y;
-
-        fn is_on_conducting_element(frame: &mut [u8], index: usize) -> bool {
-            let is_on_water = frame[index] == 0x00 // R
-                && frame[index + 1] == 0x00 // G
-                && frame[index + 2] == 0xff // B
-                && frame[index + 3] == 0xff; // A
-
-            let is_on_metal = frame[index] == 0x80 // R
-                && frame[index + 1] == 0x80 // G
-                && frame[index + 2] == 0x80 // B
-                && frame[index + 3] == 0xff; // A
-
-            return is_on_water || is_on_metal;
+/// Ácido: dissolve os vizinhos corrosíveis, gastando parte da sua força a cada um. Retorna `true` caso o ácido tenha se esgotado
+fn corrode(
+    part: &mut Particle,
+    x: u32,
+    y: u32,
+    world: &World,
+    rng: &mut SimRng,
+    commands: &mut Vec<Command>,
+) -> bool {
+    let State::Strength(strength) = &mut part.state else {
+        return false;
+    };
+    for (nx, ny) in world.neighbours(x, y) {
+        let corrodibility = world.get(nx, ny).map_or(0.0, |neighbour| {
+            world.material(neighbour.kind()).corrodibility
+        });
+        if rng.gen::<f32>() < corrodibility {
+            commands.push(Command::Remove(nx, ny));
+            *strength -= 1;
+            if *strength == 0 {
+                commands.push(Command::Remove(x, y));
+                return true;
+            }
         }
+    }
+    false
+}
 
-        if direction == 0 && self.x > 0 {
-            let index_left = position_to_index(self.x - 1, self.y);
+/// Fonte: emite o seu material em uma célula vizinha livre escolhida ao acaso
+fn emit(
+    emits: ParticleNum,
+    x: u32,
+    y: u32,
+    world: &World,
+    rng: &mut SimRng,
+    commands: &mut Vec<Command>,
+) {
+    let free: Vec<(u32, u32)> = world
+        .neighbours(x, y)
+        .filter(|&(nx, ny)| world.is_empty(nx, ny))
+        .collect();
+    if free.is_empty() {
+        return;
+    }
+    let (nx, ny) = free[rng.gen_range(0, free.len())];
+    commands.push(Command::Spawn(nx, ny, Particle::new(emits)));
+}
 
-            if is_on_conducting_element(frame, index_left) {
-                new_x = self.x - 1;
-                new_y = self.y;
+impl BaseParticle for Particle {
+    fn move_particle(
+        &mut self,
+        x: &mut u32,
+        y: &mut u32,
+        world: &World,
+        rng: &mut SimRng,
+        commands: &mut Vec<Command>,
+    ) {
+        let kind = self.kind();
+        //Os materiais explosivos (com força de explosão no arquivo de materiais, como a pólvora) explodem ao serem tocados pelo fogo
+        //ou pela eletricidade; enquanto isso, se movem normalmente
+        if let Some(strength) = world.material(kind).explosion_strength {
+            if self.colision(*x, *y, world) {
+                commands.push(Command::Explode(*x, *y, strength));
+                return;
             }
         }
-        if direction == 1 && self.x < WIDTH - 1 {
-            let index_right = position_to_index(self.x + 1, self.y);
-            if is_on_conducting_element(frame, index_right) {
-                new_x = self.x + 1;
-                new_y = self.y;
+        match kind {
+            //A partícula base apenas cai, sem escorregar para as diagonais
+            ParticleNum::Base => {
+                if self.vy >= 0.0 && self.colision(*x, *y, world) {
+                    self.vx = 0.0;
+                    self.vy = 0.0;
+                    return;
+                }
+                fall(kind, x, y, &mut self.vx, &mut self.vy, world);
             }
-        }
-        if direction == 2 && self.y > 0 {
-            let index_up = position_to_index(self.x, self.y - 1);
-            if is_on_conducting_element(frame, index_up) {
-                new_x = self.x;
-                new_y = self.y - 1;
+            ParticleNum::Agitated => move_agitated(x, y, world, rng),
+            //A faísca não se move, apenas se esgota. A descarga nos condutores vizinhos é feita por World::conduct
+            ParticleNum::Electricity => {
+                if wear_out(&mut self.state, 1) {
+                    commands.push(Command::Remove(*x, *y));
+                }
             }
-        }
-        if direction == 3 && self.y < HEIGHT - 1 {
-            let index_down = position_to_index(self.x, self.y + 1);
-            if is_on_conducting_element(frame, index_down) {
-                new_x = self.x;
-                new_y = self.y + 1;
+            ParticleNum::Fire => burn(self, x, y, world, rng, commands),
+            //A fumaça sobe e se dissipa com o tempo
+            ParticleNum::Smoke => {
+                if wear_out(&mut self.state, rng.gen::<bool>() as u8) {
+                    commands.push(Command::Remove(*x, *y));
+                    return;
+                }
+                move_gas(kind, x, y, world, rng);
             }
-        }
-        if new_x != self.x || new_y != self.y {
-            self.x = new_x;
-            self.y = new_y;
+            //O vapor sobe como a fumaça e vira água ao ficar preso no teto. Ao esfriar, ele se condensa pela mudança de estado
+            //"cools_into" do arquivo de materiais
+            ParticleNum::Steam => {
+                let rose = move_gas(kind, x, y, world, rng);
+                let on_ceiling = !rose && self.colision(*x, *y, world);
+                if on_ceiling && rng.gen::<f32>() < STEAM_CONDENSE_CHANCE {
+                    commands.push(Command::Replace(*x, *y, Particle::new(ParticleNum::Water)));
+                }
+            }
+            //A lava incendeia os vizinhos inflamáveis e cai como a água, mas só escorre para os lados em parte dos passos. O contato
+            //com a água e com a areia é tratado pelas reações "lava + water" e "lava + sand"
+            ParticleNum::Lava => {
+                ignite_neighbours(*x, *y, world, rng, commands);
+                let falling =
+                    self.vy < 0.0 || (*y + 1 < world.height() && world.can_enter(kind, *x, *y + 1));
+                if !falling && rng.gen::<f32>() < LAVA_VISCOSITY {
+                    self.vx = 0.0;
+                    self.vy = 0.0;
+                    return;
+                }
+                move_liquid(kind, x, y, &mut self.vx, &mut self.vy, world, rng);
+            }
+            ParticleNum::Acid => {
+                if corrode(self, *x, *y, world, rng, commands) {
+                    return;
+                }
+                move_liquid(kind, x, y, &mut self.vx, &mut self.vy, world, rng);
+            }
+            ParticleNum::Source => {
+                if let State::Emits(emits) = self.state {
+                    emit(emits, *x, *y, world, rng, commands);
+                }
+            }
+            //Os demais materiais se movem de acordo com a sua classe de comportamento. Os sólidos não se movem; o vazio apaga as
+            //partículas que entram nele em World::place, e as mudanças de estado do gelo e da neve são feitas por World::change_phases
+            _ => match world.material(kind).behaviour {
+                Behaviour::Powder => move_powder(kind, x, y, &mut self.vx, &mut self.vy, world),
+                Behaviour::Liquid => {
+                    move_liquid(kind, x, y, &mut self.vx, &mut self.vy, world, rng)
+                }
+                Behaviour::Gas => {
+                    move_gas(kind, x, y, world, rng);
+                }
+                Behaviour::Solid => {}
+            },
         }
     }
 
-    fn colision(&self, _: &mut [u8]) -> bool {
-        return false;
+    fn colision(&self, x: u32, y: u32, world: &World) -> bool {
+        match self.kind() {
+            //Os explosivos "colidem" com o fogo, com faíscas e condutores carregados, ou com uma célula quente o suficiente
+            kind if world.material(kind).explosion_strength.is_some() => {
+                world.temperature(x, y) > GUNPOWDER_IGNITION_POINT
+                    || world.neighbours(x, y).any(|(nx, ny)| {
+                        world.charge(nx, ny) > 0
+                            || world.is_kind(nx, ny, ParticleNum::Fire)
+                            || world.is_kind(nx, ny, ParticleNum::Electricity)
+                    })
+            }
+            //A partícula base colide com o chão e com o que não consegue atravessar
+            ParticleNum::Base => {
+                y + 1 >= world.height() || !world.can_enter(ParticleNum::Base, x, y + 1)
+            }
+            //Para que as partículas agitadas não grudem na borda, a colisão com a mesma é desconsiderada
+            ParticleNum::Agitated => false,
+            //O teto do vapor é a borda de cima do mundo ou qualquer material que não seja um fluido
+            ParticleNum::Steam => {
+                y == 0
+                    || world
+                        .get(x, y - 1)
+                        .is_some_and(|part| !world.material(part.kind()).is_fluid())
+            }
+            kind => match world.material(kind).behaviour {
+                Behaviour::Powder | Behaviour::Liquid => y + 1 >= world.height(),
+                Behaviour::Gas | Behaviour::Solid => false,
+            },
+        }
     }
 }
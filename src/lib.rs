@@ -0,0 +1,27 @@
+//! Biblioteca da simulação de partículas, sem nenhuma dependência de janela ou input.
+//!
+//! Contém os tipos de partícula ([particle]), a implementação de seus movimentos ([implparticles]) e a grade que guarda o estado
+//! da simulação ([world]), as definições dos materiais lidas de um arquivo de dados ([material]), além da leitura das opções de execução ([config]), do relógio de passo fixo ([clock]) e do pincel de inserção de partículas ([brush]). O binário `sandbox` é apenas uma camada de janela (winit) e renderização (pixels) sobre ela, permitindo que
+//! a simulação também seja executada em testes, ferramentas e servidores sem tela.
+//!
+//! ```
+//! use sandbox::particle::ParticleNum;
+//! use sandbox::world::{seeded_rng, World};
+//!
+//! let mut rng = seeded_rng(42);
+//! let mut world = World::new(20, 10);
+//! world.spawn(ParticleNum::Sand, 10, 0);
+//! for _ in 0..20 {
+//!     world.step(&mut rng);
+//! }
+//! assert!(world.is_kind(10, world.height() - 1, ParticleNum::Sand));
+//! ```
+#![deny(clippy::all)]
+
+pub mod brush;
+pub mod clock;
+pub mod config;
+pub mod implparticles;
+pub mod material;
+pub mod particle;
+pub mod world;
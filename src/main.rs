@@ -1,5 +1,7 @@
 //! Módulo principal, Lógica de execução, renderização, inicialização e chamada às funções.
 //!
+//! O binário é apenas uma camada de janela e input sobre a biblioteca `sandbox`, que contém toda a simulação.
+//!
 //! # Bibliotecas e extras
 //! Para realização do projeto, utilizamos a biblioteca Pixels <https://docs.rs/pixels/latest/pixels/> para abstrair a necessidade de criação de shaders com o wgpu, permitindo
 //! uma renderização mais simplificada. Para o Handling de entrada e criação de janelas, foi utilizada a biblioteca winit, <https://docs.rs/winit/latest/winit/> , que
@@ -11,15 +13,19 @@
 
 use log::error;
 use pixels::{Error, Pixels, SurfaceTexture};
+use sandbox::brush::Brush;
+use sandbox::clock::FixedTimestep;
+use sandbox::config::{Config, USAGE};
+use sandbox::material::{default_materials, MaterialTable};
+use sandbox::particle::*;
+use sandbox::world::{seeded_rng, World};
+use std::sync::Arc;
+use std::time::Instant;
 use winit::dpi::LogicalSize;
 use winit::event::{Event, VirtualKeyCode};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::WindowBuilder;
 use winit_input_helper::WinitInputHelper;
-mod particle;
-use particle::*;
-mod implparticles;
-use crate::implparticles::*;
 
 fn main() -> Result<(), Error> {
     //! Execução Prinicipal
@@ -60,15 +66,58 @@ fn main() -> Result<(), Error> {
     //!
     //! # Update & Renderização
     //!
-    //! Respectivamente ocorrem chamadas para as funções [update] e [draw] para execução desses trechos.
+    //! Respectivamente ocorrem chamadas para [World::step] e [World::draw] da biblioteca `sandbox` para execução desses trechos.
+    //! O update não depende dos eventos de input: a cada volta do loop de eventos o [FixedTimestep] converte o tempo decorrido em
+    //! um número fixo de passos, de forma que a velocidade da simulação não depende do mouse nem da taxa de quadros
+    //!
+    //! ```
+    //! if let Event::MainEventsCleared = event {
+    //!     let now = Instant::now();
+    //!     for _ in 0..clock.advance(now - last_frame) {
+    //!         world.step(&mut rng);
+    //!     }
+    //!     last_frame = now;
+    //!     window.request_redraw();
+    //! }
+    //! ```
     env_logger::init();
+    let config = match Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let materials = match &config.materials {
+        Some(path) => {
+            let loaded = std::fs::read_to_string(path)
+                .map_err(|e| format!("não foi possível ler {}: {}", path, e))
+                .and_then(|text| MaterialTable::from_toml(&text));
+            match loaded {
+                Ok(table) => Arc::new(table),
+                Err(message) => {
+                    eprintln!("{}", message);
+                    std::process::exit(2);
+                }
+            }
+        }
+        None => default_materials(),
+    };
     let mut clickflag: bool = true;
     let mut particlekey: ParticleNum = ParticleNum::Sand;
+    let mut emitted: ParticleNum = ParticleNum::Water;
+    let mut brush = Brush::default();
+    let mut cursor: Option<(u32, u32)> = None;
+    let mut eraser: bool = false;
+    let mut erase_filter: bool = false;
+    let mut temperature_overlay: bool = false;
     let event_loop = EventLoop::new();
     let mut input = WinitInputHelper::new();
     let window = {
-        let size = LogicalSize::new(WIDTH as f64, HEIGHT as f64);
-        let scaled_size = LogicalSize::new(WIDTH as f64 * 2.0, HEIGHT as f64 * 2.0);
+        let size = LogicalSize::new(config.width as f64, config.height as f64);
+        let scale = config.scale as f64;
+        let scaled_size =
+            LogicalSize::new(config.width as f64 * scale, config.height as f64 * scale);
         WindowBuilder::new()
             .with_title("Sandbox")
             .with_inner_size(scaled_size)
@@ -79,17 +128,30 @@ fn main() -> Result<(), Error> {
     let mut pixels = {
         let window_size = window.inner_size();
         let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
-        Pixels::new(WIDTH, HEIGHT, surface_texture)?
+        Pixels::new(config.width, config.height, surface_texture)?
     };
 
-    let mut particlevec: Vec<ParticleType> = Vec::new();
-    println!("1: Base ; 2: Areia ; 3: Ferro ; 4: Água ; 5: Agitada ; 6: Eletricidade ; P: Troca de modo de clique ; C: Limpa todas as particulas da tela");
+    let mut world = World::with_materials(config.width, config.height, materials);
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut rng = seeded_rng(seed);
+    let mut clock = FixedTimestep::new(config.ticks_per_second);
+    let mut last_frame = Instant::now();
+    println!("Semente: {} (reproduza com --seed {})", seed, seed);
+    println!("1: Base ; 2: Areia ; 3: Ferro ; 4: Água ; 5: Agitada ; 6: Eletricidade ; 7: Fogo ; 8: Vapor ; 9: Fumaça ; 0: Lava ; O: Óleo ; A: Ácido ; I: Gelo ; S: Neve ; H: Aquecedor ; K: Resfriador ; G: Planta ; W: Madeira ; B: Pólvora ; E: Fonte do material atual ; V: Vazio ; Tab: Próximo material (inclusive os do arquivo de materiais) ; P: Troca de modo de clique ; C: Limpa todas as particulas da tela");
+    println!("Botão direito: Apaga as partículas sob o pincel ; X: Borracha no botão esquerdo ; Z: Apaga apenas o material atual");
+    println!("Espaço: Pausa ; N: Avança um passo (pausado) ; +/-: Velocidade da simulação ; T: Mostra a temperatura");
+    println!("Roda do mouse ou [/]: Tamanho do pincel ; F: Forma do pincel (círculo, quadrado, spray) ; ,/.: Densidade do spray");
     event_loop.run(move |event, _, control_flow| {
-        // println!("Number of particles: {}", particlevec.len());
+        *control_flow = ControlFlow::Poll;
+        // println!("Number of particles: {}", world.particle_count());
 
         // Draw the current frame
         if let Event::RedrawRequested(_) = event {
-            draw(pixels.get_frame_mut(), particlevec.clone());
+            let frame = pixels.get_frame_mut();
+            world.draw(frame, temperature_overlay);
+            if let Some((x, y)) = cursor {
+                brush.draw_outline(frame, x, y, world.width(), world.height());
+            }
             if pixels
                 .render()
                 .map_err(|e| error!("pixels.render() failed: {}", e))
@@ -129,297 +191,183 @@ fn main() -> Result<(), Error> {
             if input.key_pressed(VirtualKeyCode::Key6) {
                 particlekey = ParticleNum::Electricity;
             }
+            if input.key_pressed(VirtualKeyCode::Key7) {
+                particlekey = ParticleNum::Fire;
+            }
+            if input.key_pressed(VirtualKeyCode::Key8) {
+                particlekey = ParticleNum::Steam;
+            }
+            if input.key_pressed(VirtualKeyCode::Key9) {
+                particlekey = ParticleNum::Smoke;
+            }
+            if input.key_pressed(VirtualKeyCode::Key0) {
+                particlekey = ParticleNum::Lava;
+            }
+            if input.key_pressed(VirtualKeyCode::O) {
+                particlekey = ParticleNum::Oil;
+            }
+            if input.key_pressed(VirtualKeyCode::A) {
+                particlekey = ParticleNum::Acid;
+            }
+            if input.key_pressed(VirtualKeyCode::I) {
+                particlekey = ParticleNum::Ice;
+            }
+            if input.key_pressed(VirtualKeyCode::S) {
+                particlekey = ParticleNum::Snow;
+            }
+            if input.key_pressed(VirtualKeyCode::H) {
+                particlekey = ParticleNum::Heater;
+            }
+            if input.key_pressed(VirtualKeyCode::K) {
+                particlekey = ParticleNum::Cooler;
+            }
+            if input.key_pressed(VirtualKeyCode::G) {
+                particlekey = ParticleNum::Plant;
+            }
+            if input.key_pressed(VirtualKeyCode::W) {
+                particlekey = ParticleNum::Wood;
+            }
+            if input.key_pressed(VirtualKeyCode::B) {
+                particlekey = ParticleNum::Gunpowder;
+            }
+            if input.key_pressed(VirtualKeyCode::E)
+                && !matches!(particlekey, ParticleNum::Source | ParticleNum::Void)
+            {
+                emitted = particlekey;
+                particlekey = ParticleNum::Source;
+            }
+            if input.key_pressed(VirtualKeyCode::V) {
+                particlekey = ParticleNum::Void;
+            }
+            // Cycle through every material of the table, including the ones defined only in data
+            if input.key_pressed(VirtualKeyCode::Tab) {
+                particlekey = world.materials().next(particlekey);
+                println!("Material: {}", world.material(particlekey).name);
+            }
+            // Brush size, shape and spray density
+            if input.scroll_diff() != 0.0 {
+                brush.resize(input.scroll_diff().signum() as i32);
+            }
+            if input.key_pressed(VirtualKeyCode::RBracket) {
+                brush.resize(1);
+            }
+            if input.key_pressed(VirtualKeyCode::LBracket) {
+                brush.resize(-1);
+            }
+            if input.key_pressed(VirtualKeyCode::F) {
+                brush.next_shape();
+                println!("Pincel: {:?}", brush.shape);
+            }
+            if input.key_pressed(VirtualKeyCode::Period) {
+                brush.set_density(brush.density * 2.0);
+                println!("Densidade do spray: {}", brush.density);
+            }
+            if input.key_pressed(VirtualKeyCode::Comma) {
+                brush.set_density(brush.density / 2.0);
+                println!("Densidade do spray: {}", brush.density);
+            }
+            if input.key_pressed(VirtualKeyCode::T) {
+                temperature_overlay = !temperature_overlay;
+            }
+            // Eraser tool and material filter
+            if input.key_pressed(VirtualKeyCode::X) {
+                eraser = !eraser;
+                println!("Borracha: {}", if eraser { "ligada" } else { "desligada" });
+            }
+            if input.key_pressed(VirtualKeyCode::Z) {
+                erase_filter = !erase_filter;
+                if erase_filter {
+                    println!("Apagando apenas: {}", world.material(particlekey).name);
+                } else {
+                    println!("Apagando todos os materiais");
+                }
+            }
             if input.key_pressed(VirtualKeyCode::C) {
-                particlevec.clear();
+                world.clear();
+            }
+            if input.key_pressed(VirtualKeyCode::Space) {
+                clock.toggle_pause();
+            }
+            if input.key_pressed(VirtualKeyCode::N) {
+                clock.single_step();
+            }
+            if input.key_pressed(VirtualKeyCode::Equals) || input.key_pressed(VirtualKeyCode::Plus)
+            {
+                clock.set_speed(clock.speed() * 2.0);
+                println!("Velocidade: {}x", clock.speed());
+            }
+            if input.key_pressed(VirtualKeyCode::Minus) {
+                clock.set_speed(clock.speed() / 2.0);
+                println!("Velocidade: {}x", clock.speed());
             }
 
-            if clickflag {
-                if input.mouse_held(0) {
-                    let pixref = &mut pixels as *mut Pixels;
-                    match instanceparticle(&input, pixref, particlekey) {
-                        Some(instancia) => {
-                            particlevec.push(instancia);
-                        }
-                        None => {}
-                    }
-                }
+            cursor = mouse_cell(&input, &pixels);
+            let (left, right) = if clickflag {
+                (input.mouse_held(0), input.mouse_held(1))
             } else {
-                if input.mouse_pressed(0) {
-                    let pixref = &mut pixels as *mut Pixels;
-                    match instanceparticle(&input, pixref, particlekey) {
-                        Some(instancia) => {
-                            particlevec.push(instancia);
-                        }
-                        None => {}
+                (input.mouse_pressed(0), input.mouse_pressed(1))
+            };
+            if let Some((x, y)) = cursor {
+                let cells =
+                    brush.cells(x, y, world.width(), world.height(), &mut rand::thread_rng());
+                if right || (left && eraser) {
+                    let filter = erase_filter.then_some(particlekey);
+                    for (x, y) in cells {
+                        world.erase(x, y, filter);
+                    }
+                } else if left {
+                    for (x, y) in cells {
+                        world.insert(x, y, instanceparticle(particlekey, emitted));
                     }
                 }
             }
             if let Some(size) = input.window_resized() {
                 pixels.resize_surface(size.width, size.height).unwrap();
             }
+        }
 
-            update(particlevec.as_mut_slice(), pixels.get_frame_mut());
-
+        // Advance the simulation at a fixed rate, independent of input events
+        if let Event::MainEventsCleared = event {
+            let now = Instant::now();
+            for _ in 0..clock.advance(now - last_frame) {
+                world.step(&mut rng);
+            }
+            last_frame = now;
             window.request_redraw();
         }
     });
 }
 
-/// # Instanciação de Partículas
+/// # Posição do Mouse
 ///
-/// Inicialmente pega as coordenadas do mouse e utiliza [position_to_index] para associar ao frame
-/// ```
-/// let mousepos = (*input).mouse().unwrap();
-/// let pixelpos = (*pixels).window_pos_to_pixel(mousepos).unwrap_or_else(|pos| (*pixels).clamp_pixel_pos(pos));
-/// let index: usize = position_to_index(pixelpos.0 as u32, pixelpos.1 as u32);
-/// ```
-///
-/// Em seguida, verifica se a partícula que vai ser instanciada não irá sobrepor outra do mesmo tipo devido a velocidade do processamento,
-/// após isso cria e retorna a nova partícula com as novas coordenadas e seus valores de cores
-/// ```
-/// ParticleNum::Sand => {
-///     let is_on_sand = frame[index] == 0x96 // R
-///     && frame[index + 1] == 0x4b // G
-///     && frame[index + 2] == 0x00 // B
-///     && frame[index + 3] == 0xff; // A
-///     if is_on_sand {
-///     return None;
-///     }
-///     let novaparticula = SandParticle {
-///     x: pixelpos.0 as u32,
-///     y: pixelpos.1 as u32,
-///     rgba: [0x96, 0x4b, 0x00, 0xff],
-///     };
-///     return Some(ParticleType::SandParticle(novaparticula));
-/// }
+/// Pega as coordenadas do mouse e as converte para uma posição do mundo. Fora da janela, a posição é levada para a borda mais próxima
 /// ```
-
-pub fn instanceparticle(
-    input: *const WinitInputHelper,
-    pixels: *mut Pixels,
-    particlekey: ParticleNum,
-) -> Option<ParticleType> {
-    unsafe {
-        let mousepos = (*input).mouse().unwrap();
-        let pixelpos = (*pixels)
-            .window_pos_to_pixel(mousepos)
-            .unwrap_or_else(|pos| (*pixels).clamp_pixel_pos(pos));
-        let index: usize = position_to_index(pixelpos.0 as u32, pixelpos.1 as u32);
-        let frame: &mut [u8] = (*pixels).get_frame_mut();
-
-        match particlekey {
-            ParticleNum::Base => {
-                let is_on_base = frame[index] == 0x00 // R
-                    && frame[index + 1] == 0xef // G
-                    && frame[index + 2] == 0x00 // B
-                    && frame[index + 3] == 0xff; // A
-
-                if is_on_base {
-                    return None;
-                }
-
-                let novaparticula = Particle {
-                    x: pixelpos.0 as u32,
-                    y: pixelpos.1 as u32,
-                    rgba: [0x00, 0xef, 0x00, 0xff],
-                };
-                return Some(ParticleType::Particle(novaparticula));
-            }
-            ParticleNum::Sand => {
-                let is_on_sand = frame[index] == 0x96 // R
-                    && frame[index + 1] == 0x4b // G
-                    && frame[index + 2] == 0x00 // B
-                    && frame[index + 3] == 0xff; // A
-
-                if is_on_sand {
-                    return None;
-                }
-
-                let novaparticula = SandParticle {
-                    x: pixelpos.0 as u32,
-                    y: pixelpos.1 as u32,
-                    rgba: [0x96, 0x4b, 0x00, 0xff],
-                };
-                return Some(ParticleType::SandParticle(novaparticula));
-            }
-            ParticleNum::Iron => {
-                let is_on_iron = frame[index] == 0x80 // R
-                && frame[index + 1] == 0x80 // G
-                && frame[index + 2] == 0x80 // B
-                && frame[index + 3] == 0xff; // A
-
-                if is_on_iron {
-                    return None;
-                }
-
-                let novaparticula = IronParticle {
-                    x: pixelpos.0 as u32,
-                    y: pixelpos.1 as u32,
-                    rgba: [0x80, 0x80, 0x80, 0xff],
-                };
-                return Some(ParticleType::IronParticle(novaparticula));
-            }
-            ParticleNum::Water => {
-                let is_on_water = frame[index] == 0x0 // R
-                    && frame[index + 1] == 0x0 // G
-                    && frame[index + 2] == 0xff // B
-                    && frame[index + 3] == 0xff; // A
-
-                if is_on_water {
-                    return None;
-                }
-
-                let novaparticula = WaterParticle {
-                    x: pixelpos.0 as u32,
-                    y: pixelpos.1 as u32,
-                    rgba: [0x0, 0x0, 0xff, 0xff],
-                };
-                return Some(ParticleType::WaterParticle(novaparticula));
-            }
-            ParticleNum::Agitated => {
-                let is_on_agitated = frame[index] == 0x16 // R
-                    && frame[index + 1] == 0x16 // G
-                    && frame[index + 2] == 0x00 // B
-                    && frame[index + 3] == 0xff; // A
-
-                if is_on_agitated {
-                    return None;
-                }
-
-                let novaparticula = AgitatedParticle {
-                    x: pixelpos.0 as u32,
-                    y: pixelpos.1 as u32,
-                    rgba: [0x16, 0x16, 0x00, 0xff],
-                };
-                return Some(ParticleType::AgitatedParticle(novaparticula));
-            }
-            ParticleNum::Electricity => {
-                let is_on_electricity = frame[index] == 0xff // R
-                    && frame[index + 1] == 0xff // G
-                    && frame[index + 2] == 0x00 // B
-                    && frame[index + 3] == 0xff; // A
-
-                if is_on_electricity {
-                    return None;
-                }
-
-                let novaparticula = ElectricityParticle {
-                    x: pixelpos.0 as u32,
-                    y: pixelpos.1 as u32,
-                    life_time: 5,
-                    rgba: [0xff, 0xff, 0x00, 0xff],
-                };
-                return Some(ParticleType::ElectricityParticle(novaparticula));
-            }
-        }
-    }
-}
-
-/// # Atualização de Partículas
-///
-/// Para cada partícula do vetor realiza o match de acordo com o tipo e chama sua função de movimentação
-/// ```text
-///     for partenum in vec {
-///         match partenum {
-///             ParticleType::SandParticle(part) => {
-///                 part.move_particle(frame);
-///                 }
-///          ...
-///          ...
+/// let mousepos = input.mouse().unwrap();
+/// let pixelpos = pixels.window_pos_to_pixel(mousepos).unwrap_or_else(|pos| pixels.clamp_pixel_pos(pos));
+/// let (x, y) = (pixelpos.0 as u32, pixelpos.1 as u32);
 /// ```
-pub fn update(vec: &mut [ParticleType], frame: &mut [u8]) {
-    for partenum in vec {
-        match partenum {
-            ParticleType::SandParticle(part) => {
-                part.move_particle(frame);
-            }
-            ParticleType::Particle(part) => {
-                part.move_particle(frame);
-            }
-            ParticleType::IronParticle(part) => {
-                part.move_particle(frame);
-            }
-            ParticleType::WaterParticle(part) => {
-                part.move_particle(frame);
-            }
-            ParticleType::AgitatedParticle(part) => {
-                part.move_particle(frame);
-            }
-            ParticleType::ElectricityParticle(part) => {
-                part.move_particle(frame);
-            }
-        }
-    }
+pub fn mouse_cell(input: &WinitInputHelper, pixels: &Pixels) -> Option<(u32, u32)> {
+    let mousepos = input.mouse()?;
+    let pixelpos = pixels
+        .window_pos_to_pixel(mousepos)
+        .unwrap_or_else(|pos| pixels.clamp_pixel_pos(pos));
+    Some((pixelpos.0 as u32, pixelpos.1 as u32))
 }
 
-/// # Renderização
-///
-/// Inicialmente limpa a tela, preenchendo todos os componentes dos pixels com o valor 150
-/// ```
-/// frame.fill(150);
-/// ```
+/// # Instanciação de Partículas
 ///
-/// Em seguida, para cada partícula instanciada, dá match de acordo com seu tipo, utiliza [position_to_index] para pegar os valores de posição
-/// da partícula e associar a índices no frame, em seguida preenche as componetes rgba do píxel de acordo com as cores da partícula
+/// Cria a partícula do material selecionado com [Particle::new]. Uma fonte é criada emitindo o material `emitted`.
 ///
+/// A partícula é criada para cada célula coberta pelo [Brush] em volta do [cursor](mouse_cell), e só é de fato adicionada caso a célula
+/// esteja livre: as células ocupadas são recusadas por [World::insert] e contadas em [World::rejected_placements]
 /// ```
-/// ParticleType::SandParticle(part) => {
-///     let index: usize = position_to_index(part.x, part.y);
-///     frame[index] = part.rgba[0]; //r
-///     frame[index + 1] = part.rgba[1]; //g
-///     frame[index + 2] = part.rgba[2]; //b
-///     frame[index + 3] = part.rgba[3]; //a
-///     }
+/// for (x, y) in brush.cells(x, y, world.width(), world.height(), &mut rand::thread_rng()) {
+///     world.insert(x, y, instanceparticle(particlekey, emitted));
+/// }
 /// ```
-///
-pub fn draw(frame: &mut [u8], vec: Vec<ParticleType>) {
-    //clear(frame);
-    frame.fill(150);
-
-    for partenum in vec {
-        match partenum {
-            ParticleType::SandParticle(part) => {
-                let index: usize = position_to_index(part.x, part.y);
-                frame[index] = part.rgba[0]; //r
-                frame[index + 1] = part.rgba[1]; //g
-                frame[index + 2] = part.rgba[2]; //b
-                frame[index + 3] = part.rgba[3]; //a
-            }
-            ParticleType::Particle(part) => {
-                let index: usize = position_to_index(part.x, part.y);
-                frame[index] = part.rgba[0]; //r
-                frame[index + 1] = part.rgba[1]; //g
-                frame[index + 2] = part.rgba[2]; //b
-                frame[index + 3] = part.rgba[3]; //a
-            }
-            ParticleType::IronParticle(part) => {
-                let index: usize = position_to_index(part.x, part.y);
-                frame[index] = part.rgba[0]; //r
-                frame[index + 1] = part.rgba[1]; //g
-                frame[index + 2] = part.rgba[2]; //b
-                frame[index + 3] = part.rgba[3]; //a
-            }
-            ParticleType::WaterParticle(part) => {
-                let index: usize = position_to_index(part.x, part.y);
-                frame[index] = part.rgba[0]; //r
-                frame[index + 1] = part.rgba[1]; //g
-                frame[index + 2] = part.rgba[2]; //b
-                frame[index + 3] = part.rgba[3]; //a
-            }
-            ParticleType::AgitatedParticle(part) => {
-                let index: usize = position_to_index(part.x, part.y);
-                frame[index] = part.rgba[0]; //r
-                frame[index + 1] = part.rgba[1]; //g
-                frame[index + 2] = part.rgba[2]; //b
-                frame[index + 3] = part.rgba[3]; //a
-            }
-            ParticleType::ElectricityParticle(part) => {
-                let index: usize = position_to_index(part.x, part.y);
-                frame[index] = part.rgba[0]; //r
-                frame[index + 1] = part.rgba[1]; //g
-                frame[index + 2] = part.rgba[2]; //b
-                frame[index + 3] = part.rgba[3]; //a
-            }
-        }
+pub fn instanceparticle(particlekey: ParticleNum, emitted: ParticleNum) -> Particle {
+    match particlekey {
+        ParticleNum::Source => Particle::emitting(emitted),
+        _ => Particle::new(particlekey),
     }
 }
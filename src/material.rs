@@ -0,0 +1,456 @@
+//! Definição dos materiais, lida de um arquivo de dados TOML na inicialização.
+//!
+//! As propriedades de cada material (cor, densidade, classe de comportamento, inflamabilidade, condutividade, reações...) não ficam no
+//! código: elas são lidas do arquivo `materials.toml`, embutido no binário, e podem ser alteradas ou estendidas por outro arquivo com a
+//! opção `--materials`. Um material do arquivo com o mesmo nome de um material padrão o substitui; um nome novo cria um material
+//! [personalizado](ParticleNum::Custom), que se move de acordo com a sua [classe de comportamento](Behaviour).
+//!
+//! O mesmo arquivo declara as [reações](Reaction) entre pares de materiais vizinhos, aplicadas a cada passo por
+//! [World::react](crate::world::World::react). Uma regra do arquivo para o mesmo par de materiais de uma regra padrão a substitui.
+//!
+//! ```toml
+//! [[material]]
+//! name = "salt"
+//! color = "#f0f0f0"
+//! density = 2.1
+//! behaviour = "powder"
+//!
+//! [[reaction]]
+//! reactants = ["salt", "water"]
+//! products = ["empty", "water"]
+//! chance = 0.01
+//! ```
+//!
+//! Cada [World](crate::world::World) guarda a sua própria tabela, compartilhada por um [Arc], e consulta as propriedades dos materiais
+//! (densidade, inflamabilidade...) por ela com [World::material](crate::world::World::material). Assim, mundos diferentes no mesmo
+//! processo, como os de testes ou de um servidor, podem usar tabelas diferentes.
+//!
+//! ```
+//! use sandbox::material::MaterialTable;
+//! use sandbox::world::World;
+//! use std::sync::Arc;
+//!
+//! let table = MaterialTable::from_toml(
+//!     r##"
+//!     [[material]]
+//!     name = "mud"
+//!     color = "#5c4033"
+//!     density = 1.8
+//!     behaviour = "liquid"
+//!     "##,
+//! )
+//! .unwrap();
+//! let mut world = World::with_materials(20, 10, Arc::new(table));
+//! let mud = world.materials().find("mud").unwrap();
+//! world.spawn(mud, 10, 0);
+//! assert!(World::new(20, 10).materials().find("mud").is_none());
+//! ```
+use crate::particle::{ParticleNum, MAX_MATERIALS};
+use serde::Deserialize;
+use std::sync::{Arc, OnceLock};
+
+/// Arquivo de materiais padrão, embutido no binário
+pub const DEFAULT_MATERIALS: &str = include_str!("../materials.toml");
+
+/// Nome usado nos produtos das reações para uma célula vazia
+pub const EMPTY: &str = "empty";
+
+/// Classe de comportamento de um material
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Behaviour {
+    /// Cai e forma pirâmides de apoio, como a areia
+    Powder,
+    /// Cai e escorre para os lados, como a água
+    Liquid,
+    /// Sobe e se espalha para os lados, como a fumaça
+    Gas,
+    /// Não se move
+    Solid,
+}
+
+/// Mudança de estado de um material ao passar de uma temperatura limite
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PhaseChange {
+    /// Temperatura limite, em °C
+    pub limit: f32,
+    /// Material em que o material se transforma
+    pub into: ParticleNum,
+}
+
+/// Resultado de uma reação para uma das células envolvidas
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Product {
+    /// A partícula da célula é removida
+    Empty,
+    /// A partícula da célula é transformada no material informado. Um produto igual ao reagente deixa a partícula como está
+    Material(ParticleNum),
+}
+
+/// Reação entre dois materiais vizinhos: a cada passo, com chance `chance`, o par `reactants` vira o par `products`
+///
+/// ```toml
+/// [[reaction]]
+/// reactants = ["lava", "water"]
+/// products = ["stone", "steam"]
+/// chance = 1.0
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Reaction {
+    /// Materiais das duas células vizinhas
+    pub reactants: [ParticleNum; 2],
+    /// O que acontece com cada célula, na ordem dos reagentes
+    pub products: [Product; 2],
+    /// Chance da reação acontecer a cada passo, entre 0 e 1
+    pub chance: f32,
+}
+
+impl Reaction {
+    /// A mesma reação com os reagentes (e os produtos) em ordem inversa
+    pub fn reversed(&self) -> Reaction {
+        Reaction {
+            reactants: [self.reactants[1], self.reactants[0]],
+            products: [self.products[1], self.products[0]],
+            chance: self.chance,
+        }
+    }
+}
+
+/// Definição completa de um material
+#[derive(Clone, PartialEq, Debug)]
+pub struct Material {
+    /// Nome do material, usado para referenciá-lo no arquivo de dados
+    pub name: String,
+    /// Cor da partícula
+    pub rgba: [u8; 4],
+    /// Densidade, em g/cm³
+    pub density: f32,
+    /// Classe de comportamento
+    pub behaviour: Behaviour,
+    /// Chance, a cada passo, de pegar fogo ao lado do fogo
+    pub flammability: f32,
+    /// Material deixado pelo fogo ao terminar de queimar
+    pub burns_into: Option<ParticleNum>,
+    /// Condutividade térmica, entre 0 e 1
+    pub conductivity: f32,
+    /// Indica se o material conduz a carga elétrica
+    pub conductor: bool,
+    /// Chance, a cada passo, de ser dissolvido pelo ácido
+    pub corrodibility: f32,
+    /// Temperatura mantida pelo material na sua célula
+    pub heat_source: Option<f32>,
+    /// Temperatura da célula onde o material é inserido
+    pub initial_temperature: Option<f32>,
+    /// Mudança de estado ao esquentar acima do limite
+    pub heats_into: Option<PhaseChange>,
+    /// Mudança de estado ao esfriar abaixo do limite
+    pub cools_into: Option<PhaseChange>,
+    /// Raio da explosão do material, em células
+    pub explosion_strength: Option<f32>,
+    /// Indica se o material resiste às explosões
+    pub blast_resistant: bool,
+}
+
+impl Material {
+    /// Verifica se o material é um fluido (líquido ou gás), podendo ser deslocado por materiais mais densos
+    pub fn is_fluid(&self) -> bool {
+        matches!(self.behaviour, Behaviour::Liquid | Behaviour::Gas)
+    }
+}
+
+/// Tabela de materiais, indexada por [ParticleNum::id]. Os materiais padrão ocupam os primeiros índices, na ordem de
+/// [ParticleNum::BUILTIN], seguidos dos materiais personalizados. Guarda também as reações entre os materiais, com no máximo
+/// uma reação por par
+#[derive(Clone, PartialEq, Debug)]
+pub struct MaterialTable {
+    materials: Vec<Material>,
+    reactions: Vec<Reaction>,
+    /// Índice em `reactions` da reação de cada par de materiais, indexado por `a.id() * materials.len() + b.id()`
+    pairs: Vec<Option<usize>>,
+}
+
+impl Default for MaterialTable {
+    /// Tabela lida do arquivo de materiais padrão
+    fn default() -> Self {
+        MaterialTable::from_toml("").expect("o arquivo de materiais padrão é válido")
+    }
+}
+
+impl MaterialTable {
+    /// Lê um arquivo de materiais no formato TOML, aplicado por cima dos [materiais padrão](DEFAULT_MATERIALS)
+    ///
+    /// ```
+    /// use sandbox::material::{Behaviour, MaterialTable};
+    ///
+    /// let table = MaterialTable::from_toml(
+    ///     r##"
+    ///     [[material]]
+    ///     name = "mud"
+    ///     color = "#5c4033"
+    ///     density = 1.8
+    ///     behaviour = "liquid"
+    ///     "##,
+    /// )
+    /// .unwrap();
+    /// let mud = table.find("mud").unwrap();
+    /// assert_eq!(table.get(mud).behaviour, Behaviour::Liquid);
+    /// ```
+    pub fn from_toml(text: &str) -> Result<MaterialTable, String> {
+        let defaults = parse(DEFAULT_MATERIALS)?;
+        let file = parse(text)?;
+        let mut definitions = defaults.material;
+        for definition in file.material {
+            match definitions
+                .iter_mut()
+                .find(|old| old.name == definition.name)
+            {
+                Some(old) => *old = definition,
+                None => definitions.push(definition),
+            }
+        }
+        let mut rules = defaults.reaction;
+        for rule in file.reaction {
+            let [a, b] = &rule.reactants;
+            match rules.iter_mut().find(|old| {
+                old.reactants == [a.clone(), b.clone()] || old.reactants == [b.clone(), a.clone()]
+            }) {
+                Some(old) => *old = rule,
+                None => rules.push(rule),
+            }
+        }
+
+        let mut names: Vec<String> = Vec::with_capacity(definitions.len());
+        for (kind, name) in ParticleNum::BUILTIN {
+            if !definitions.iter().any(|definition| definition.name == name) {
+                return Err(format!("material padrão ausente: {}", name));
+            }
+            debug_assert_eq!(kind.id(), names.len());
+            names.push(name.to_string());
+        }
+        for definition in &definitions {
+            if !names.contains(&definition.name) {
+                names.push(definition.name.clone());
+            }
+        }
+        if names.len() > MAX_MATERIALS {
+            return Err(format!(
+                "materiais demais: {}, o máximo é {}",
+                names.len(),
+                MAX_MATERIALS
+            ));
+        }
+
+        let find = |name: &str| -> Result<ParticleNum, String> {
+            names
+                .iter()
+                .position(|known| known == name)
+                .map(ParticleNum::from_id)
+                .ok_or_else(|| format!("material desconhecido: {}", name))
+        };
+        let product = |name: &str| -> Result<Product, String> {
+            match name {
+                EMPTY => Ok(Product::Empty),
+                name => find(name).map(Product::Material),
+            }
+        };
+
+        let mut materials = Vec::with_capacity(names.len());
+        for name in &names {
+            let definition = definitions
+                .iter()
+                .find(|definition| &definition.name == name)
+                .expect("todo nome vem de uma definição");
+            materials.push(Material {
+                name: name.clone(),
+                rgba: parse_color(&definition.color)?,
+                density: definition.density,
+                behaviour: definition.behaviour,
+                flammability: definition.flammability,
+                burns_into: definition.burns_into.as_deref().map(find).transpose()?,
+                conductivity: definition.conductivity,
+                conductor: definition.conductor,
+                corrodibility: definition.corrodibility,
+                heat_source: definition.heat_source,
+                initial_temperature: definition.initial_temperature,
+                heats_into: definition
+                    .heats_into
+                    .as_ref()
+                    .map(|change| {
+                        find(&change.into).map(|into| PhaseChange {
+                            limit: change.above,
+                            into,
+                        })
+                    })
+                    .transpose()?,
+                cools_into: definition
+                    .cools_into
+                    .as_ref()
+                    .map(|change| {
+                        find(&change.into).map(|into| PhaseChange {
+                            limit: change.below,
+                            into,
+                        })
+                    })
+                    .transpose()?,
+                explosion_strength: definition.explosion_strength,
+                blast_resistant: definition.blast_resistant,
+            });
+        }
+
+        let count = materials.len();
+        let mut reactions = Vec::with_capacity(rules.len());
+        let mut pairs = vec![None; count * count];
+        for rule in &rules {
+            let reaction = Reaction {
+                reactants: [find(&rule.reactants[0])?, find(&rule.reactants[1])?],
+                products: [product(&rule.products[0])?, product(&rule.products[1])?],
+                chance: rule.chance,
+            };
+            let [a, b] = reaction.reactants;
+            pairs[a.id() * count + b.id()] = Some(reactions.len());
+            pairs[b.id() * count + a.id()] = Some(reactions.len());
+            reactions.push(reaction);
+        }
+        Ok(MaterialTable {
+            materials,
+            reactions,
+            pairs,
+        })
+    }
+
+    /// Definição do material. `kind` precisa ser um material desta tabela, por exemplo vindo de [MaterialTable::find]
+    pub fn get(&self, kind: ParticleNum) -> &Material {
+        &self.materials[kind.id()]
+    }
+
+    /// Material com o nome informado, caso exista
+    pub fn find(&self, name: &str) -> Option<ParticleNum> {
+        self.materials
+            .iter()
+            .position(|material| material.name == name)
+            .map(ParticleNum::from_id)
+    }
+
+    /// Todos os materiais da tabela, na ordem dos seus índices
+    pub fn kinds(&self) -> impl Iterator<Item = ParticleNum> {
+        (0..self.materials.len()).map(ParticleNum::from_id)
+    }
+
+    /// Material seguinte a `kind` na tabela, voltando ao primeiro depois do último
+    pub fn next(&self, kind: ParticleNum) -> ParticleNum {
+        ParticleNum::from_id((kind.id() + 1) % self.materials.len())
+    }
+
+    /// Todas as reações da tabela
+    pub fn reactions(&self) -> &[Reaction] {
+        &self.reactions
+    }
+
+    /// Reação entre os materiais `a` e `b`, caso exista, com os reagentes na ordem `[a, b]`
+    ///
+    /// ```
+    /// use sandbox::material::{MaterialTable, Product};
+    /// use sandbox::particle::ParticleNum;
+    ///
+    /// let table = MaterialTable::default();
+    /// let reaction = table.reaction_between(ParticleNum::Water, ParticleNum::Lava).unwrap();
+    /// assert_eq!(
+    ///     reaction.products,
+    ///     [Product::Material(ParticleNum::Steam), Product::Material(ParticleNum::Stone)]
+    /// );
+    /// assert!(table.reaction_between(ParticleNum::Water, ParticleNum::Iron).is_none());
+    /// ```
+    pub fn reaction_between(&self, a: ParticleNum, b: ParticleNum) -> Option<Reaction> {
+        let index = self.pairs[a.id() * self.materials.len() + b.id()]?;
+        let reaction = self.reactions[index];
+        if reaction.reactants[0] == a {
+            Some(reaction)
+        } else {
+            Some(reaction.reversed())
+        }
+    }
+}
+
+/// Tabela de materiais padrão, lida uma única vez e compartilhada pelos mundos criados com [World::new](crate::world::World::new).
+/// Como a tabela padrão nunca muda, compartilhá-la não impede que outros mundos usem outras tabelas
+pub fn default_materials() -> Arc<MaterialTable> {
+    static DEFAULT: OnceLock<Arc<MaterialTable>> = OnceLock::new();
+    DEFAULT
+        .get_or_init(|| Arc::new(MaterialTable::default()))
+        .clone()
+}
+
+/// Arquivo de materiais, como escrito em TOML
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialFile {
+    #[serde(default)]
+    material: Vec<MaterialDef>,
+    #[serde(default)]
+    reaction: Vec<ReactionDef>,
+}
+
+/// Definição de um material como escrita no arquivo, referenciando outros materiais pelo nome
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialDef {
+    name: String,
+    color: String,
+    density: f32,
+    behaviour: Behaviour,
+    #[serde(default)]
+    flammability: f32,
+    burns_into: Option<String>,
+    #[serde(default)]
+    conductivity: f32,
+    #[serde(default)]
+    conductor: bool,
+    #[serde(default)]
+    corrodibility: f32,
+    heat_source: Option<f32>,
+    initial_temperature: Option<f32>,
+    heats_into: Option<HeatDef>,
+    cools_into: Option<CoolDef>,
+    explosion_strength: Option<f32>,
+    #[serde(default)]
+    blast_resistant: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HeatDef {
+    above: f32,
+    into: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CoolDef {
+    below: f32,
+    into: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReactionDef {
+    reactants: [String; 2],
+    products: [String; 2],
+    chance: f32,
+}
+
+/// Lê as definições de um arquivo de materiais
+fn parse(text: &str) -> Result<MaterialFile, String> {
+    toml::from_str::<MaterialFile>(text)
+        .map_err(|error| format!("arquivo de materiais inválido: {}", error))
+}
+
+/// Lê uma cor no formato `#rrggbb`
+fn parse_color(color: &str) -> Result<[u8; 4], String> {
+    let invalid = || format!("cor inválida: {}, esperado #rrggbb", color);
+    let hex = color.strip_prefix('#').ok_or_else(invalid)?;
+    if hex.len() != 6 {
+        return Err(invalid());
+    }
+    let value = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+    Ok([(value >> 16) as u8, (value >> 8) as u8, value as u8, 0xff])
+}
@@ -1,27 +1,23 @@
-//! Definição das Structs para cada Partícula, declaração das traits básicas de movimento e colisão, definição de Enums para os tipos das partículas.
+//! Definição da partícula, do seu estado e dos materiais, e declaração das traits básicas de movimento e colisão.
 //!
-//! Toda Partícula possui a mesma estrutura base, contendo posições x,y e uma cor
-//! ```
+//! Toda partícula é um [Particle] compacto, o mesmo para todos os materiais: o material ([ParticleNum]), a velocidade e o estado
+//! próprio do material ([State]). A posição e a cor não são guardadas na partícula: a posição é a célula do [World] que a contém e a
+//! cor vem da [definição do material](crate::material), na tabela de materiais do [World]. O comportamento de cada material é
+//! escolhido a partir do material em [BaseParticle::move_particle], de forma que um material novo não precisa de uma struct nova.
+//! ```ignore
 //! pub struct Particle {
-//!     pub x: u32,
-//!     pub y: u32,
-//!     pub rgba: [u8; 4],
+//!     kind: ParticleNum,
+//!     pub vx: f32,
+//!     pub vy: f32,
+//!     pub state: State,
 //! }
 //! ```
 
-///Enumeration Tipada, constructo específico do rust que se comporta como uma Union de C, utilizada para construir um vetor heterogêneo de partículas
-#[derive(Copy, Clone)]
-pub enum ParticleType {
-    Particle(Particle),
-    SandParticle(SandParticle),
-    IronParticle(IronParticle),
-    WaterParticle(WaterParticle),
-    AgitatedParticle(AgitatedParticle),
-    ElectricityParticle(ElectricityParticle),
-}
+use crate::material::MaterialTable;
+use crate::world::{Command, SimRng, World};
 
 ///Enumeration Tradicional, utilizada para associar tipos às teclas e para realizar o switch
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum ParticleNum {
     Base,
     Sand,
@@ -29,61 +25,233 @@ pub enum ParticleNum {
     Water,
     Agitated,
     Electricity,
+    Fire,
+    Ash,
+    Smoke,
+    Steam,
+    Lava,
+    Stone,
+    Glass,
+    Oil,
+    Acid,
+    Ice,
+    Snow,
+    Heater,
+    Cooler,
+    Plant,
+    Wood,
+    Gunpowder,
+    Source,
+    Void,
+    /// Material definido apenas no arquivo de materiais. Só pode ser obtido da [MaterialTable], por exemplo com
+    /// [MaterialTable::find], ou com [ParticleNum::from_id]
+    Custom(CustomId),
 }
 
-///Base, apenas cai
-#[derive(Copy, Clone)]
-pub struct Particle {
-    pub x: u32,
-    pub y: u32,
-    pub rgba: [u8; 4],
-}
+/// Índice de um [material personalizado](ParticleNum::Custom) na [MaterialTable]. O índice é opaco, para que um material
+/// personalizado não possa ser criado fora da tabela
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CustomId(u16);
 
-///Areia, cai e forma pirâmide de apoio
-#[derive(Copy, Clone)]
-pub struct SandParticle {
-    pub x: u32,
-    pub y: u32,
-    pub rgba: [u8; 4],
-}
+impl ParticleNum {
+    /// Materiais definidos no código, com o nome usado no arquivo de materiais, na ordem dos seus índices na [MaterialTable]
+    pub const BUILTIN: [(ParticleNum, &'static str); 24] = [
+        (ParticleNum::Base, "base"),
+        (ParticleNum::Sand, "sand"),
+        (ParticleNum::Iron, "iron"),
+        (ParticleNum::Water, "water"),
+        (ParticleNum::Agitated, "agitated"),
+        (ParticleNum::Electricity, "electricity"),
+        (ParticleNum::Fire, "fire"),
+        (ParticleNum::Ash, "ash"),
+        (ParticleNum::Smoke, "smoke"),
+        (ParticleNum::Steam, "steam"),
+        (ParticleNum::Lava, "lava"),
+        (ParticleNum::Stone, "stone"),
+        (ParticleNum::Glass, "glass"),
+        (ParticleNum::Oil, "oil"),
+        (ParticleNum::Acid, "acid"),
+        (ParticleNum::Ice, "ice"),
+        (ParticleNum::Snow, "snow"),
+        (ParticleNum::Heater, "heater"),
+        (ParticleNum::Cooler, "cooler"),
+        (ParticleNum::Plant, "plant"),
+        (ParticleNum::Wood, "wood"),
+        (ParticleNum::Gunpowder, "gunpowder"),
+        (ParticleNum::Source, "source"),
+        (ParticleNum::Void, "void"),
+    ];
+
+    /// Índice do material na [MaterialTable]
+    pub fn id(&self) -> usize {
+        match self {
+            ParticleNum::Base => 0,
+            ParticleNum::Sand => 1,
+            ParticleNum::Iron => 2,
+            ParticleNum::Water => 3,
+            ParticleNum::Agitated => 4,
+            ParticleNum::Electricity => 5,
+            ParticleNum::Fire => 6,
+            ParticleNum::Ash => 7,
+            ParticleNum::Smoke => 8,
+            ParticleNum::Steam => 9,
+            ParticleNum::Lava => 10,
+            ParticleNum::Stone => 11,
+            ParticleNum::Glass => 12,
+            ParticleNum::Oil => 13,
+            ParticleNum::Acid => 14,
+            ParticleNum::Ice => 15,
+            ParticleNum::Snow => 16,
+            ParticleNum::Heater => 17,
+            ParticleNum::Cooler => 18,
+            ParticleNum::Plant => 19,
+            ParticleNum::Wood => 20,
+            ParticleNum::Gunpowder => 21,
+            ParticleNum::Source => 22,
+            ParticleNum::Void => 23,
+            ParticleNum::Custom(CustomId(id)) => *id as usize,
+        }
+    }
 
-///Ferro, não se move
-#[derive(Copy, Clone)]
-pub struct IronParticle {
-    pub x: u32,
-    pub y: u32,
-    pub rgba: [u8; 4],
+    /// Material com o índice informado na [MaterialTable]. Uma tabela tem no máximo [MAX_MATERIALS] materiais
+    pub fn from_id(id: usize) -> ParticleNum {
+        match ParticleNum::BUILTIN.get(id) {
+            Some((kind, _)) => *kind,
+            None => ParticleNum::Custom(CustomId(
+                u16::try_from(id).expect("índice de material fora da tabela"),
+            )),
+        }
+    }
 }
 
-///Cai e escorrega para os lados tentando cair
-#[derive(Copy, Clone)]
-pub struct WaterParticle {
-    pub x: u32,
-    pub y: u32,
-    pub rgba: [u8; 4],
+/// Número máximo de materiais de uma [MaterialTable], limitado pelo tamanho do [CustomId]
+pub const MAX_MATERIALS: usize = u16::MAX as usize + 1;
+
+/// Passos de vida de uma faísca recém criada
+pub const ELECTRICITY_LIFE_TIME: u8 = 5;
+/// Passos de vida de um fogo recém criado
+pub const FIRE_LIFE_TIME: u8 = 40;
+/// Passos de vida de uma fumaça recém criada
+pub const SMOKE_LIFE_TIME: u8 = 80;
+/// Quantas partículas um ácido recém criado consegue dissolver antes de se esgotar
+pub const ACID_STRENGTH: u8 = 3;
+
+/// Cores entre as quais o fogo tremula
+pub const FLAMES: [[u8; 4]; 3] = [
+    [0xff, 0x45, 0x00, 0xff],
+    [0xff, 0x8c, 0x00, 0xff],
+    [0xff, 0xd7, 0x00, 0xff],
+];
+
+/// Estado próprio de uma partícula, que depende do seu material
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum State {
+    /// Material sem estado próprio
+    None,
+    /// Passos restantes até a partícula sumir: a faísca se apaga, a fumaça se dissipa
+    LifeTime(u8),
+    /// Fogo: passos restantes até se apagar e o material deixado no seu lugar, ver [Material::burns_into](crate::material::Material::burns_into)
+    Burning {
+        life_time: u8,
+        residue: Option<ParticleNum>,
+    },
+    /// Ácido: partículas que ainda podem ser dissolvidas até ele se esgotar
+    Strength(u8),
+    /// Fonte: material emitido
+    Emits(ParticleNum),
 }
 
-///Se move aleatoriamente
-#[derive(Copy, Clone)]
-pub struct AgitatedParticle {
-    pub x: u32,
-    pub y: u32,
-    pub rgba: [u8; 4],
+/// Partícula de qualquer material
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Particle {
+    kind: ParticleNum,
+    /// Velocidade horizontal, em células por passo. Usada apenas pelos pós e líquidos
+    pub vx: f32,
+    /// Velocidade vertical, em células por passo, positiva para baixo. Usada apenas pelos pós e líquidos
+    pub vy: f32,
+    /// Estado próprio do material
+    pub state: State,
 }
 
-///Parada, quando entra em contato com água se move dentro dela
-#[derive(Copy, Clone)]
-pub struct ElectricityParticle {
-    pub x: u32,
-    pub y: u32,
-    pub life_time: u8,
-    pub rgba: [u8; 4],
+impl Particle {
+    /// Cria uma partícula parada do material informado, com o estado inicial do material
+    pub fn new(kind: ParticleNum) -> Particle {
+        let state = match kind {
+            ParticleNum::Electricity => State::LifeTime(ELECTRICITY_LIFE_TIME),
+            ParticleNum::Fire => State::Burning {
+                life_time: FIRE_LIFE_TIME,
+                residue: None,
+            },
+            ParticleNum::Smoke => State::LifeTime(SMOKE_LIFE_TIME),
+            ParticleNum::Acid => State::Strength(ACID_STRENGTH),
+            ParticleNum::Source => State::Emits(ParticleNum::Water),
+            _ => State::None,
+        };
+        Particle {
+            kind,
+            vx: 0.0,
+            vy: 0.0,
+            state,
+        }
+    }
+
+    /// Fogo que consome uma partícula, deixando `residue` ao se apagar. O resíduo de cada material é o seu
+    /// [burns_into](crate::material::Material::burns_into)
+    pub fn ignite(residue: Option<ParticleNum>) -> Particle {
+        Particle {
+            state: State::Burning {
+                life_time: FIRE_LIFE_TIME,
+                residue,
+            },
+            ..Particle::new(ParticleNum::Fire)
+        }
+    }
+
+    /// Fonte que emite o material informado
+    pub fn emitting(material: ParticleNum) -> Particle {
+        Particle {
+            state: State::Emits(material),
+            ..Particle::new(ParticleNum::Source)
+        }
+    }
+
+    /// Material da partícula
+    pub fn kind(&self) -> ParticleNum {
+        self.kind
+    }
+
+    /// Soma (vx, vy) à velocidade da partícula, por exemplo ao ser arremessada por uma explosão. A velocidade só é seguida pelos pós e
+    /// pelos líquidos, que caem
+    pub fn push(&mut self, vx: f32, vy: f32) {
+        self.vx += vx;
+        self.vy += vy;
+    }
+
+    /// Cor da partícula: a cor do material na tabela `materials`, exceto pelo fogo, que tremula entre as [FLAMES] ao longo da sua vida
+    pub fn rgba(&self, materials: &MaterialTable) -> [u8; 4] {
+        match self.state {
+            State::Burning { life_time, .. } => FLAMES[life_time as usize % FLAMES.len()],
+            _ => materials.get(self.kind).rgba,
+        }
+    }
 }
 
-///Trait base para todas as partículas
+///Trait base das partículas
+///
+/// As partículas consultam apenas o [World] para decidir seu movimento; o frame da tela não é lido durante a simulação.
 pub trait BaseParticle {
-    /// Função de movimento da partícula
-    fn move_particle(&mut self, frame: &mut [u8]);
-    /// Função de colisão da partícula
-    fn colision(&self, frame: &mut [u8]) -> bool;
+    /// Função de movimento da partícula que está na célula x,y: a partícula se move alterando `x` e `y`, e toda decisão aleatória
+    /// deve usar `rng`.
+    ///
+    /// Criação e remoção de partículas (inclusive da própria) são pedidas empilhando [Command]s em `commands`
+    fn move_particle(
+        &mut self,
+        x: &mut u32,
+        y: &mut u32,
+        world: &World,
+        rng: &mut SimRng,
+        commands: &mut Vec<Command>,
+    );
+    /// Função de colisão da partícula que está na célula x,y
+    fn colision(&self, x: u32, y: u32, world: &World) -> bool;
 }
@@ -0,0 +1,805 @@
+//! Definição do [World], a grade de ocupação que guarda o estado da simulação.
+//!
+//! Cada célula da grade é indexada por x,y e guarda a partícula que a ocupa (seu material e estado). A física consulta apenas essa grade,
+//! sendo o frame da tela apenas uma projeção dela feita em [World::draw].
+//!
+//! As dimensões do mundo são escolhidas em tempo de execução e carregadas pelo próprio [World] ([World::width], [World::height]).
+//!
+//! O avanço da simulação é feito por [World::step], que não depende de janela nem de input.
+//!
+//! # Invariante de ocupação
+//!
+//! Cada célula guarda no máximo uma partícula. Como a partícula não guarda a sua posição, que é a própria célula, duas partículas nunca
+//! ocupam a mesma posição. Inserções em células ocupadas são recusadas e contadas em [World::rejected_placements], e movimentos para
+//! células ocupadas só acontecem como troca de lugar entre as duas partículas.
+//!
+//! # Carga elétrica
+//!
+//! Cada célula guarda também uma carga elétrica, que se propaga como uma onda pelos [condutores](Material::conductor) conectados
+//! (ver [World::conduct]). Uma célula carregada com [CHARGE] é a frente da onda; nos passos seguintes sua carga decai até 0, formando um
+//! rastro visível que impede a onda de voltar para trás.
+//!
+//! # Temperatura
+//!
+//! Cada célula guarda uma temperatura, em °C, que é conduzida para as células vizinhas a cada passo de acordo com a
+//! [condutividade](Material::conductivity) dos materiais (ver [World::diffuse_heat]). As
+//! [fontes e sumidouros de calor](Material::heat_source) mantêm a sua célula em uma temperatura fixa, e as células vazias voltam aos
+//! poucos para a [AMBIENT_TEMPERATURE]. Ao se mover, uma partícula leva consigo a temperatura da sua célula, de forma que a neve continua
+//! fria enquanto cai.
+//!
+//! As mudanças de estado (água que ferve ou congela, gelo que derrete, lava que esfria) são definidas por material em
+//! [Material::heats_into] e [Material::cools_into] e aplicadas a cada passo por [World::change_phases]. Como o ar leva tudo o que
+//! está parado nele para a [AMBIENT_TEMPERATURE], os limites de aquecimento ficam acima dela: o gelo e a neve só derretem perto de
+//! fontes de calor.
+//!
+//! # Reações
+//!
+//! As interações entre dois materiais vizinhos que não dependem do estado das partículas (a lava que solidifica na água, a água que
+//! apaga o fogo, a planta que cresce...) são [reações](crate::material::Reaction) declaradas no arquivo de materiais e aplicadas a cada passo por
+//! [World::react]. As reações carregadas podem ser consultadas com [MaterialTable::reaction_between].
+//!
+//! # Materiais
+//!
+//! As propriedades dos materiais vêm da [tabela de materiais](MaterialTable) do mundo, recebida na criação com
+//! [World::with_materials] e consultada com [World::material]. Mundos criados com [World::new] usam a
+//! [tabela padrão](default_materials).
+//!
+//! ```text
+//! (0,0) (1,0) (2,0) ... (width-1,0)
+//! (0,1) (1,1) (2,1) ... (width-1,1)
+//!  ...
+//! ```
+use crate::material::{default_materials, Behaviour, Material, MaterialTable, Product};
+use crate::particle::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::sync::Arc;
+
+/// Gerador de números aleatórios da simulação. Passado explicitamente para [World::step], de forma que a mesma semente e as mesmas
+/// entradas produzem sempre o mesmo mundo.
+///
+/// O algoritmo é fixado (ChaCha8) em vez de usar o `StdRng` do rand, que pode mudar entre versões e plataformas, para que uma semente
+/// reproduza a mesma execução em qualquer máquina
+pub type SimRng = ChaCha8Rng;
+
+/// Cria o gerador da simulação a partir de uma semente
+pub fn seeded_rng(seed: u64) -> SimRng {
+    SimRng::seed_from_u64(seed)
+}
+
+/// Carga da frente de uma onda elétrica
+pub const CHARGE: u8 = 6;
+/// Cor do rastro deixado pela carga elétrica
+pub const CHARGE_COLOR: [u8; 4] = [0xff, 0xff, 0x00, 0xff];
+
+/// Temperatura ambiente, em °C, das células de um mundo novo
+pub const AMBIENT_TEMPERATURE: f32 = 20.0;
+/// Condutividade térmica de uma célula vazia, ver [Material::conductivity]
+pub const AIR_CONDUCTIVITY: f32 = 0.02;
+/// Fração da diferença para a [AMBIENT_TEMPERATURE] que a temperatura de uma célula vazia percorre a cada passo
+pub const AIR_COOLING: f32 = 0.02;
+
+/// Alteração no mundo pedida por uma partícula durante seu movimento.
+///
+/// As partículas recebem o mundo apenas para leitura; para criar ou destruir partículas (evaporação, queima, dissolução, emissores...)
+/// elas empilham comandos, aplicados com [World::apply] logo após a partícula ser recolocada na grade.
+#[derive(Copy, Clone)]
+pub enum Command {
+    /// Cria a partícula na célula x,y, caso a célula esteja livre
+    Spawn(u32, u32, Particle),
+    /// Remove a partícula da célula x,y, caso exista
+    Remove(u32, u32),
+    /// Substitui o conteúdo da célula x,y pela partícula informada, por exemplo a água que evapora
+    Replace(u32, u32, Particle),
+    /// Explosão centrada na célula x,y com a força informada, ver [World::explode]
+    Explode(u32, u32, f32),
+}
+
+impl Command {
+    /// Célula alvo do comando
+    pub fn cell(&self) -> (u32, u32) {
+        match *self {
+            Command::Spawn(x, y, _)
+            | Command::Remove(x, y)
+            | Command::Replace(x, y, _)
+            | Command::Explode(x, y, _) => (x, y),
+        }
+    }
+}
+
+/// Célula da grade
+#[derive(Copy, Clone)]
+pub struct Cell {
+    /// Partícula que ocupa a célula, `None` caso a célula esteja vazia
+    pub particle: Option<Particle>,
+    /// Carga elétrica da célula, entre 0 e [CHARGE]
+    pub charge: u8,
+    /// Temperatura da célula, em °C
+    pub temperature: f32,
+    /// Indica se a partícula da célula já foi atualizada no passo corrente, evitando que uma partícula se mova duas vezes no mesmo passo
+    pub updated: bool,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell {
+            particle: None,
+            charge: 0,
+            temperature: AMBIENT_TEMPERATURE,
+            updated: false,
+        }
+    }
+}
+
+/// Grade de ocupação, fonte única de verdade para a física das partículas
+#[derive(Clone)]
+pub struct World {
+    width: u32,
+    height: u32,
+    cells: Vec<Cell>,
+    rejected: u64,
+    materials: Arc<MaterialTable>,
+}
+
+/// Largura padrão do mundo
+pub const DEFAULT_WIDTH: u32 = 200;
+/// Altura padrão do mundo
+pub const DEFAULT_HEIGHT: u32 = 150;
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new(DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+}
+
+/// Tamanho, em bytes, do frame rgba de um mundo `width` x `height`, ou `None` caso ele não caiba na memória endereçável (`usize`)
+pub fn frame_len(width: u32, height: u32) -> Option<usize> {
+    (width as usize)
+        .checked_mul(height as usize)?
+        .checked_mul(4)
+}
+
+impl World {
+    /// Cria um mundo vazio com dimensões `width` x `height`, usando a [tabela de materiais padrão](default_materials). O
+    /// [frame](frame_len) do mundo precisa caber em um `usize`
+    pub fn new(width: u32, height: u32) -> World {
+        Self::with_materials(width, height, default_materials())
+    }
+
+    /// Cria um mundo vazio com dimensões `width` x `height`, cujas partículas seguem a tabela de materiais `materials`
+    pub fn with_materials(width: u32, height: u32, materials: Arc<MaterialTable>) -> World {
+        assert!(
+            width > 0 && height > 0,
+            "o mundo precisa ter ao menos uma célula"
+        );
+        assert!(
+            frame_len(width, height).is_some(),
+            "o mundo {}x{} é grande demais",
+            width,
+            height
+        );
+        World {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+            rejected: 0,
+            materials,
+        }
+    }
+
+    /// Tabela de materiais do mundo
+    pub fn materials(&self) -> &MaterialTable {
+        &self.materials
+    }
+
+    /// Definição do material `kind` na tabela do mundo
+    pub fn material(&self, kind: ParticleNum) -> &Material {
+        self.materials.get(kind)
+    }
+
+    /// Largura do mundo, em células
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Altura do mundo, em células
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn cell_index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    fn cell_mut(&mut self, x: u32, y: u32) -> &mut Cell {
+        let index = self.cell_index(x, y);
+        &mut self.cells[index]
+    }
+
+    /// Verifica se a posição está dentro dos limites do mundo
+    pub fn in_bounds(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Partícula na posição x,y, caso exista
+    pub fn get(&self, x: u32, y: u32) -> Option<&Particle> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.cells[self.cell_index(x, y)].particle.as_ref()
+    }
+
+    /// Verifica se a célula x,y existe e está vazia
+    pub fn is_empty(&self, x: u32, y: u32) -> bool {
+        self.in_bounds(x, y) && self.cells[self.cell_index(x, y)].particle.is_none()
+    }
+
+    /// Verifica se a célula x,y existe e contém uma partícula do tipo informado
+    pub fn is_kind(&self, x: u32, y: u32, kind: ParticleNum) -> bool {
+        self.get(x, y).map(|part| part.kind()) == Some(kind)
+    }
+
+    /// Insere a partícula na célula x,y.
+    ///
+    /// Caso a célula esteja ocupada ou fora do mundo, a inserção é recusada, contabilizada em [World::rejected_placements] e `false` é retornado.
+    /// Materiais com [temperatura própria](Material::initial_temperature) levam a célula para essa temperatura.
+    pub fn insert(&mut self, x: u32, y: u32, particle: Particle) -> bool {
+        if !self.is_empty(x, y) {
+            self.rejected += 1;
+            return false;
+        }
+        let initial_temperature = self.material(particle.kind()).initial_temperature;
+        let cell = self.cell_mut(x, y);
+        cell.particle = Some(particle);
+        if let Some(temperature) = initial_temperature {
+            cell.temperature = temperature;
+        }
+        true
+    }
+
+    /// Número de inserções recusadas por [World::insert], por a célula já estar ocupada ou estar fora do mundo. Inclui as partículas
+    /// pedidas pelas próprias partículas com [Command::Spawn], como as emitidas por uma fonte bloqueada
+    pub fn rejected_placements(&self) -> u64 {
+        self.rejected
+    }
+
+    /// Aplica um [Command] ao mundo. Partículas criadas ou substituídas durante um passo só se movem no passo seguinte
+    pub fn apply(&mut self, command: Command) {
+        match command {
+            Command::Spawn(x, y, particle) => {
+                if self.insert(x, y, particle) {
+                    self.cell_mut(x, y).updated = true;
+                }
+            }
+            Command::Remove(x, y) => {
+                self.take(x, y);
+            }
+            Command::Replace(x, y, particle) => {
+                if self.in_bounds(x, y) {
+                    let cell = self.cell_mut(x, y);
+                    cell.particle = Some(particle);
+                    cell.updated = true;
+                }
+            }
+            Command::Explode(x, y, strength) => self.explode(x, y, strength),
+        }
+    }
+
+    /// # Explosão
+    ///
+    /// Explode a célula x,y com força `strength`, que é o raio em células atingido pela explosão. Materiais
+    /// [resistentes](Material::blast_resistant) não são afetados; os demais:
+    /// - até a metade do raio, são destruídos e dão lugar ao fogo, que também ocupa as células vazias;
+    /// - no restante do raio, os pós e líquidos são [arremessados](Particle::push) para longe do centro e para cima, mais forte quanto
+    ///   mais perto do centro. Sólidos e gases não são arremessados.
+    pub fn explode(&mut self, x: u32, y: u32, strength: f32) {
+        let radius = strength.ceil() as i64;
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                if distance > strength || nx < 0 || ny < 0 || !self.in_bounds(nx as u32, ny as u32)
+                {
+                    continue;
+                }
+                let (nx, ny) = (nx as u32, ny as u32);
+                let behaviour = match self.get(nx, ny) {
+                    Some(part) if self.material(part.kind()).blast_resistant => continue,
+                    Some(part) => Some(self.material(part.kind()).behaviour),
+                    None => None,
+                };
+                let cell = self.cell_mut(nx, ny);
+                if distance <= strength / 2.0 {
+                    cell.particle = Some(Particle::new(ParticleNum::Fire));
+                    cell.updated = true;
+                } else if let (Some(part), Some(Behaviour::Powder | Behaviour::Liquid)) =
+                    (cell.particle.as_mut(), behaviour)
+                {
+                    let power = strength * (1.0 - distance / strength);
+                    part.push(
+                        power * dx as f32 / distance,
+                        power * dy as f32 / distance - power,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Vizinhos (esquerda, direita, cima, baixo) da célula x,y que estão dentro do mundo
+    pub fn neighbours(&self, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> + '_ {
+        [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(move |&(nx, ny)| self.in_bounds(nx, ny))
+    }
+
+    /// Carga elétrica da célula x,y
+    pub fn charge(&self, x: u32, y: u32) -> u8 {
+        if !self.in_bounds(x, y) {
+            return 0;
+        }
+        self.cells[self.cell_index(x, y)].charge
+    }
+
+    /// # Condução elétrica
+    ///
+    /// Avança a onda de carga em um passo, a partir das cargas do passo anterior:
+    /// - a frente da onda ([CHARGE]) e o rastro decaem uma unidade;
+    /// - um condutor descarregado vizinho de uma frente de onda ou de uma [faísca](ParticleNum::Electricity) vira uma nova frente;
+    /// - células que não contêm um condutor perdem a carga.
+    pub fn conduct(&mut self) {
+        let mut next = vec![0; self.cells.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = self.cell_index(x, y);
+                let conductor = self.cells[index]
+                    .particle
+                    .is_some_and(|part| self.material(part.kind()).conductor);
+                if !conductor {
+                    continue;
+                }
+                let charge = self.cells[index].charge;
+                next[index] = if charge > 0 {
+                    charge - 1
+                } else if self.neighbours(x, y).any(|(nx, ny)| {
+                    self.charge(nx, ny) == CHARGE || self.is_kind(nx, ny, ParticleNum::Electricity)
+                }) {
+                    CHARGE
+                } else {
+                    0
+                };
+            }
+        }
+        for (cell, charge) in self.cells.iter_mut().zip(next) {
+            cell.charge = charge;
+        }
+    }
+
+    /// Temperatura da célula x,y, em °C. Fora do mundo, a temperatura é a [AMBIENT_TEMPERATURE]
+    pub fn temperature(&self, x: u32, y: u32) -> f32 {
+        if !self.in_bounds(x, y) {
+            return AMBIENT_TEMPERATURE;
+        }
+        self.cells[self.cell_index(x, y)].temperature
+    }
+
+    /// Condutividade térmica da célula x,y, que depende do material que a ocupa
+    fn thermal_conductivity(&self, x: u32, y: u32) -> f32 {
+        self.get(x, y).map_or(AIR_CONDUCTIVITY, |part| {
+            self.material(part.kind()).conductivity
+        })
+    }
+
+    /// # Difusão de calor
+    ///
+    /// Avança a temperatura em um passo, a partir das temperaturas do passo anterior:
+    /// - uma célula com uma [fonte ou sumidouro](Material::heat_source) assume a temperatura da fonte;
+    /// - entre duas células vizinhas passa, a cada passo, um quarto da diferença de temperatura multiplicado pela menor das duas
+    ///   [condutividades](Material::conductivity): o ferro esquenta rápido, a areia devagar;
+    /// - células vazias também se aproximam da [AMBIENT_TEMPERATURE], a uma taxa [AIR_COOLING].
+    pub fn diffuse_heat(&mut self) {
+        let mut next = Vec::with_capacity(self.cells.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = &self.cells[self.cell_index(x, y)];
+                if let Some(source) = cell
+                    .particle
+                    .and_then(|part| self.material(part.kind()).heat_source)
+                {
+                    next.push(source);
+                    continue;
+                }
+                let conductivity = self.thermal_conductivity(x, y);
+                let mut temperature = cell.temperature;
+                for (nx, ny) in self.neighbours(x, y) {
+                    let rate = conductivity.min(self.thermal_conductivity(nx, ny)) / 4.0;
+                    temperature += rate * (self.temperature(nx, ny) - cell.temperature);
+                }
+                if cell.particle.is_none() {
+                    temperature += AIR_COOLING * (AMBIENT_TEMPERATURE - temperature);
+                }
+                next.push(temperature);
+            }
+        }
+        for (cell, temperature) in self.cells.iter_mut().zip(next) {
+            cell.temperature = temperature;
+        }
+    }
+
+    /// # Mudanças de estado
+    ///
+    /// Transforma cada partícula cuja célula passou do limite de temperatura do seu material ([Material::heats_into],
+    /// [Material::cools_into]), mantendo a temperatura da célula: a água a mais de 100 °C vira vapor, o vapor abaixo de 40 °C volta a ser água, a lava abaixo de 100 °C vira pedra.
+    pub fn change_phases(&mut self) {
+        for cell in self.cells.iter_mut() {
+            let Some(part) = cell.particle else {
+                continue;
+            };
+            let material = self.materials.get(part.kind());
+            let heated = material
+                .heats_into
+                .filter(|change| cell.temperature > change.limit);
+            let cooled = material
+                .cools_into
+                .filter(|change| cell.temperature < change.limit);
+            if let Some(change) = heated.or(cooled) {
+                cell.particle = Some(Particle::new(change.into));
+            }
+        }
+    }
+
+    /// # Reações
+    ///
+    /// Testa a [reação](crate::material::Reaction) de cada par de células vizinhas ocupadas, uma vez por par, com a chance da reação. Quando a reação
+    /// acontece, cada célula vira o seu [produto](Product), mantendo a sua temperatura caso o produto não tenha
+    /// [temperatura própria](Material::initial_temperature); um produto igual ao reagente deixa a partícula
+    /// como está. Cada partícula participa de no máximo uma reação por passo
+    ///
+    /// ```
+    /// use sandbox::particle::ParticleNum;
+    /// use sandbox::world::{seeded_rng, World};
+    ///
+    /// let mut world = World::new(2, 1);
+    /// world.spawn(ParticleNum::Lava, 0, 0);
+    /// world.spawn(ParticleNum::Water, 1, 0);
+    /// world.react(&mut seeded_rng(0));
+    /// assert!(world.is_kind(0, 0, ParticleNum::Stone));
+    /// assert!(world.is_kind(1, 0, ParticleNum::Steam));
+    /// ```
+    pub fn react(&mut self, rng: &mut SimRng) {
+        let table = Arc::clone(&self.materials);
+        let mut reacted = vec![false; self.cells.len()];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                    if !self.in_bounds(nx, ny) {
+                        continue;
+                    }
+                    let (index, other) = (self.cell_index(x, y), self.cell_index(nx, ny));
+                    if reacted[index] || reacted[other] {
+                        continue;
+                    }
+                    let (Some(a), Some(b)) =
+                        (self.cells[index].particle, self.cells[other].particle)
+                    else {
+                        continue;
+                    };
+                    let Some(reaction) = table.reaction_between(a.kind(), b.kind()) else {
+                        continue;
+                    };
+                    if rng.gen::<f32>() >= reaction.chance {
+                        continue;
+                    }
+                    self.transform(index, a, reaction.products[0]);
+                    self.transform(other, b, reaction.products[1]);
+                    reacted[index] = true;
+                    reacted[other] = true;
+                }
+            }
+        }
+    }
+
+    /// Aplica o produto de uma reação à partícula `part` da célula `index`. Como em [World::insert], um produto com
+    /// [temperatura própria](Material::initial_temperature) leva a célula para essa temperatura: o vapor da lava na água nasce quente
+    fn transform(&mut self, index: usize, part: Particle, product: Product) {
+        let materials = &self.materials;
+        let cell = &mut self.cells[index];
+        match product {
+            Product::Empty => cell.particle = None,
+            Product::Material(kind) if kind == part.kind() => {}
+            Product::Material(kind) => {
+                cell.particle = Some(Particle::new(kind));
+                if let Some(temperature) = materials.get(kind).initial_temperature {
+                    cell.temperature = temperature;
+                }
+            }
+        }
+    }
+
+    /// Verifica se qualquer partícula pode entrar na célula x,y sem deslocar ninguém: a célula está vazia ou é um
+    /// [vazio](ParticleNum::Void), que apaga a partícula ao chegar nele
+    pub fn is_free(&self, x: u32, y: u32) -> bool {
+        self.is_empty(x, y) || self.is_kind(x, y, ParticleNum::Void)
+    }
+
+    /// Verifica se uma partícula do tipo `mover` pode deslocar a partícula da célula x,y, trocando de lugar com ela.
+    ///
+    /// Apenas fluidos são deslocados, e apenas por materiais mais densos: a areia afunda na água, enquanto a água não afunda na água.
+    ///
+    /// Qualquer partícula pode entrar no [vazio](ParticleNum::Void), sendo apagada ao chegar nele.
+    pub fn can_displace(&self, mover: ParticleNum, x: u32, y: u32) -> bool {
+        self.get(x, y).is_some_and(|part| {
+            let target = self.material(part.kind());
+            part.kind() == ParticleNum::Void
+                || (target.is_fluid() && target.density < self.material(mover).density)
+        })
+    }
+
+    /// Verifica se uma partícula do tipo `mover` pode entrar na célula x,y, seja por estar vazia ou por poder deslocar seu ocupante
+    pub fn can_enter(&self, mover: ParticleNum, x: u32, y: u32) -> bool {
+        self.is_empty(x, y) || self.can_displace(mover, x, y)
+    }
+
+    /// Verifica se uma partícula do tipo `mover` pode subir para a célula x,y: a célula está vazia ou contém um fluido mais denso, que
+    /// afunda trocando de lugar com ela (empuxo). Usado pelos gases, que sobem através da água e de gases mais pesados
+    pub fn can_float_into(&self, mover: ParticleNum, x: u32, y: u32) -> bool {
+        self.is_empty(x, y)
+            || self.get(x, y).is_some_and(|part| {
+                let target = self.material(part.kind());
+                part.kind() == ParticleNum::Void
+                    || (target.is_fluid() && target.density > self.material(mover).density)
+            })
+    }
+
+    /// Percorre o caminho reto de `from` até `from + (dx, dy)` célula a célula, retornando a última célula alcançada antes do primeiro
+    /// obstáculo ou da borda do mundo.
+    ///
+    /// Usado nos deslocamentos de mais de uma célula por passo, para que partículas rápidas não atravessem obstáculos finos. Um fluido mais
+    /// leve que `mover` só é deslocado quando está na primeira célula do caminho, de forma que a troca de lugar é sempre entre vizinhos e
+    /// que a partícula afunda no fluido uma célula por passo.
+    pub fn trace(&self, mover: ParticleNum, from: (u32, u32), dx: i32, dy: i32) -> (u32, u32) {
+        let steps = dx.abs().max(dy.abs());
+        let mut last = from;
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let x = (from.0 as f32 + dx as f32 * t).round();
+            let y = (from.1 as f32 + dy as f32 * t).round();
+            if x < 0.0 || y < 0.0 {
+                break;
+            }
+            let (x, y) = (x as u32, y as u32);
+            if self.is_empty(x, y) {
+                last = (x, y);
+                continue;
+            }
+            if step == 1 && self.can_displace(mover, x, y) {
+                last = (x, y);
+            }
+            break;
+        }
+        last
+    }
+
+    /// Remove e retorna a partícula da célula x,y
+    pub fn take(&mut self, x: u32, y: u32) -> Option<Particle> {
+        if !self.in_bounds(x, y) {
+            return None;
+        }
+        self.cell_mut(x, y).particle.take()
+    }
+
+    /// Apaga a partícula da célula x,y, retornando-a. Com um filtro, apenas partículas do material informado são apagadas
+    ///
+    /// ```
+    /// use sandbox::particle::ParticleNum;
+    /// use sandbox::world::World;
+    ///
+    /// let mut world = World::new(2, 1);
+    /// world.spawn(ParticleNum::Sand, 0, 0);
+    /// world.spawn(ParticleNum::Water, 1, 0);
+    /// world.erase(0, 0, Some(ParticleNum::Water));
+    /// world.erase(1, 0, Some(ParticleNum::Water));
+    /// assert!(world.is_kind(0, 0, ParticleNum::Sand));
+    /// assert!(world.is_empty(1, 0));
+    /// ```
+    pub fn erase(&mut self, x: u32, y: u32, filter: Option<ParticleNum>) -> Option<Particle> {
+        match filter {
+            Some(kind) if !self.is_kind(x, y, kind) => None,
+            _ => self.take(x, y),
+        }
+    }
+
+    /// Remove todas as partículas do mundo, descarregando as células e voltando-as para a [AMBIENT_TEMPERATURE]
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+    }
+
+    /// Número de partículas no mundo
+    pub fn particle_count(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|cell| cell.particle.is_some())
+            .count()
+    }
+
+    /// Recoloca na célula `to` uma partícula que saiu da célula `from` após se mover.
+    ///
+    /// Caso o destino esteja ocupado (por exemplo, a areia afundando na água), as duas partículas trocam de lugar. As temperaturas das
+    /// duas células também são trocadas, acompanhando as partículas. Uma partícula que entra no [vazio](ParticleNum::Void) é apagada,
+    /// e `false` é retornado.
+    fn place(&mut self, particle: Particle, from: (u32, u32), to: (u32, u32)) -> bool {
+        let (x, y) = to;
+        if (x, y) != from {
+            if self.is_kind(x, y, ParticleNum::Void) {
+                return false;
+            }
+            if let Some(displaced) = self.take(x, y) {
+                let cell = self.cell_mut(from.0, from.1);
+                debug_assert!(cell.particle.is_none(), "célula de origem ocupada");
+                cell.particle = Some(displaced);
+                cell.updated = true;
+            }
+            let (source, target) = (self.cell_index(from.0, from.1), self.cell_index(x, y));
+            let temperature = self.cells[source].temperature;
+            self.cells[source].temperature = self.cells[target].temperature;
+            self.cells[target].temperature = temperature;
+        }
+        let cell = self.cell_mut(x, y);
+        debug_assert!(cell.particle.is_none(), "célula de destino ocupada");
+        cell.particle = Some(particle);
+        cell.updated = true;
+        true
+    }
+
+    /// Partícula da célula x,y, caso exista e ainda não tenha sido atualizada no passo corrente
+    fn take_pending(&mut self, x: u32, y: u32) -> Option<Particle> {
+        let cell = self.cell_mut(x, y);
+        if cell.updated {
+            return None;
+        }
+        cell.particle.take()
+    }
+
+    /// Marca todas as células como não atualizadas, preparando o próximo passo
+    fn reset_updated(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.updated = false;
+        }
+    }
+
+    /// Itera sobre todas as partículas do mundo, linha a linha
+    pub fn particles(&self) -> impl Iterator<Item = &Particle> {
+        self.cells.iter().filter_map(|cell| cell.particle.as_ref())
+    }
+
+    /// Cria a partícula do tipo informado na posição x,y, caso a célula esteja livre (ver [World::insert])
+    pub fn spawn(&mut self, kind: ParticleNum, x: u32, y: u32) -> bool {
+        self.insert(x, y, Particle::new(kind))
+    }
+
+    /// # Atualização de Partículas
+    ///
+    /// Avança a simulação em um passo, utilizando `rng` para todas as decisões aleatórias. Percorre a grade de baixo para cima, retirando cada partícula de sua célula e chamando
+    /// sua função de movimentação, que escolhe o comportamento pelo material. Em seguida a partícula é recolocada na grade em sua nova posição com `place` e os [Command]s
+    /// empilhados por ela são aplicados, podendo criar ou remover partículas. Caso a partícula tenha sido apagada pelo vazio, os comandos
+    /// para a sua nova célula são descartados, para que não sobrescrevam o vazio. Em seguida, os vizinhos reagem entre si com [World::react].
+    /// Por fim, a carga elétrica é propagada com [World::conduct] e o calor com [World::diffuse_heat], que pode mudar o estado das
+    /// partículas em [World::change_phases]
+    /// ```text
+    ///     for y in (0..self.height).rev() {
+    ///         for x in 0..self.width {
+    ///             if let Some(mut part) = self.take_pending(x, y) {
+    ///                 let (mut nx, mut ny) = (x, y);
+    ///                 part.move_particle(&mut nx, &mut ny, self, rng, &mut commands);
+    ///                 let placed = self.place(part, (x, y), (nx, ny));
+    ///                 for command in commands.drain(..) {
+    ///                     if placed || command.cell() != (nx, ny) {
+    ///                         self.apply(command);
+    ///                     }
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    /// ```
+    pub fn step(&mut self, rng: &mut SimRng) {
+        let mut commands = Vec::new();
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                let Some(mut part) = self.take_pending(x, y) else {
+                    continue;
+                };
+                let (mut nx, mut ny) = (x, y);
+                part.move_particle(&mut nx, &mut ny, self, rng, &mut commands);
+                let placed = self.place(part, (x, y), (nx, ny));
+                for command in commands.drain(..) {
+                    if placed || command.cell() != (nx, ny) {
+                        self.apply(command);
+                    }
+                }
+            }
+        }
+        self.react(rng);
+        self.reset_updated();
+        self.conduct();
+        self.diffuse_heat();
+        self.change_phases();
+    }
+
+    /// # Renderização
+    ///
+    /// Projeta a grade em um frame rgba de [World::width] x [World::height] píxeis. Inicialmente limpa o frame, preenchendo todos os componentes dos pixels com o valor 150
+    /// ```ignore
+    /// frame.fill(150);
+    /// ```
+    ///
+    /// Em seguida, como o frame tem um píxel por célula, na mesma ordem da grade (ver [position_to_index](crate::implparticles::position_to_index)), preenche as componentes
+    /// rgba do píxel de cada célula ocupada de acordo com as cores da partícula
+    ///
+    /// ```ignore
+    /// for (cell, pixel) in self.cells.iter().zip(frame.chunks_exact_mut(4)) {
+    ///     if let Some(part) = cell.particle {
+    ///         pixel.copy_from_slice(&part.rgba(&self.materials));
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Células com carga elétrica têm sua cor misturada com [CHARGE_COLOR] proporcionalmente à carga, desenhando o rastro da onda.
+    ///
+    /// Com `temperature_overlay` ligado, todas as células, vazias ou não, têm sua cor misturada com a cor da sua temperatura: azul para
+    /// o frio, escuro para a temperatura ambiente, e de vermelho a branco para o calor. A sobreposição é uma opção de visualização de
+    /// quem desenha, e não faz parte do estado do mundo.
+    pub fn draw(&self, frame: &mut [u8], temperature_overlay: bool) {
+        frame.fill(150);
+
+        for (cell, pixel) in self.cells.iter().zip(frame.chunks_exact_mut(4)) {
+            if let Some(part) = cell.particle {
+                let mut rgba = part.rgba(&self.materials);
+                if cell.charge > 0 {
+                    for (component, glow) in rgba.iter_mut().zip(CHARGE_COLOR) {
+                        *component = ((*component as u32 * (CHARGE - cell.charge) as u32
+                            + glow as u32 * cell.charge as u32)
+                            / CHARGE as u32) as u8;
+                    }
+                }
+                pixel.copy_from_slice(&rgba);
+            }
+        }
+
+        if temperature_overlay {
+            for (cell, pixel) in self.cells.iter().zip(frame.chunks_exact_mut(4)) {
+                for (component, heat) in pixel.iter_mut().zip(temperature_color(cell.temperature)) {
+                    *component = ((*component as u32 * 3 + heat as u32 * 7) / 10) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Cor de uma temperatura na sobreposição de temperatura, interpolada entre as cores de referência
+fn temperature_color(temperature: f32) -> [u8; 4] {
+    const STOPS: [(f32, [u8; 4]); 5] = [
+        (-60.0, [0x00, 0x40, 0xff, 0xff]),
+        (AMBIENT_TEMPERATURE, [0x20, 0x20, 0x20, 0xff]),
+        (100.0, [0xc0, 0x00, 0x00, 0xff]),
+        (600.0, [0xff, 0xc0, 0x00, 0xff]),
+        (1100.0, [0xff, 0xff, 0xff, 0xff]),
+    ];
+    let Some(upper) = STOPS.iter().position(|&(limit, _)| temperature < limit) else {
+        return STOPS[STOPS.len() - 1].1;
+    };
+    if upper == 0 {
+        return STOPS[0].1;
+    }
+    let (low, low_color) = STOPS[upper - 1];
+    let (high, high_color) = STOPS[upper];
+    let t = (temperature - low) / (high - low);
+    let mut rgba = low_color;
+    for (component, target) in rgba.iter_mut().zip(high_color) {
+        *component = (*component as f32 + (target as f32 - *component as f32) * t) as u8;
+    }
+    rgba
+}
@@ -0,0 +1,442 @@
+//! Testes de regressão da simulação, rodados sobre a biblioteca `sandbox` sem janela.
+use sandbox::implparticles::position_to_index;
+use sandbox::material::{default_materials, MaterialTable};
+use sandbox::particle::*;
+use sandbox::world::{seeded_rng, World, CHARGE, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+use std::sync::Arc;
+
+/// Roda `steps` passos do mundo com a semente informada
+fn run(world: &mut World, seed: u64, steps: usize) {
+    let mut rng = seeded_rng(seed);
+    for _ in 0..steps {
+        world.step(&mut rng);
+    }
+}
+
+/// Mundo com água, partículas agitadas, madeira e fogo, que usam o gerador da simulação a cada passo
+fn mixed_scenario() -> World {
+    let mut world = World::new(32, 24);
+    for x in 0..32 {
+        world.spawn(ParticleNum::Iron, x, 23);
+    }
+    for x in 2..10 {
+        for y in 0..4 {
+            world.spawn(ParticleNum::Water, x, y);
+        }
+    }
+    for x in 12..18 {
+        world.spawn(ParticleNum::Agitated, x, 10);
+    }
+    for x in 20..28 {
+        for y in 18..23 {
+            world.spawn(ParticleNum::Wood, x, y);
+        }
+    }
+    world.spawn(ParticleNum::Fire, 24, 17);
+    world
+}
+
+#[test]
+fn same_seed_produces_identical_worlds() {
+    let (mut first, mut second) = (mixed_scenario(), mixed_scenario());
+    run(&mut first, 42, 300);
+    run(&mut second, 42, 300);
+    for y in 0..first.height() {
+        for x in 0..first.width() {
+            assert_eq!(first.get(x, y), second.get(x, y), "célula {},{}", x, y);
+            assert_eq!(
+                first.temperature(x, y),
+                second.temperature(x, y),
+                "célula {},{}",
+                x,
+                y
+            );
+        }
+    }
+}
+
+/// Bloco 5x5 de gelo apoiado sobre uma linha de ferro, no meio de um mundo vazio
+fn ice_block(world: &mut World) {
+    for x in 0..world.width() {
+        world.spawn(ParticleNum::Iron, x, 19);
+    }
+    for x in 8..13 {
+        for y in 14..19 {
+            world.spawn(ParticleNum::Ice, x, y);
+        }
+    }
+}
+
+#[test]
+fn ice_does_not_melt_at_ambient_temperature() {
+    let mut world = World::new(20, 20);
+    ice_block(&mut world);
+    for x in 15..18 {
+        for y in 0..3 {
+            world.spawn(ParticleNum::Snow, x, y);
+        }
+    }
+    run(&mut world, 0, 2000);
+    let count = |kind| world.particles().filter(|part| part.kind() == kind).count();
+    assert_eq!(count(ParticleNum::Ice), 25);
+    assert_eq!(count(ParticleNum::Snow), 9);
+    assert_eq!(count(ParticleNum::Water), 0);
+}
+
+#[test]
+fn ice_melts_next_to_a_heater() {
+    let mut world = World::new(20, 20);
+    ice_block(&mut world);
+    for y in 14..19 {
+        world.spawn(ParticleNum::Heater, 13, y);
+    }
+    run(&mut world, 0, 600);
+    assert!(!world
+        .particles()
+        .any(|part| part.kind() == ParticleNum::Ice));
+}
+
+/// Despeja um jorro de lava de 4 células de largura do topo de um mundo `width` x `height` com chão de ferro, retornando quantas
+/// células da linha logo acima do chão têm lava
+fn pour_lava(width: u32, height: u32) -> usize {
+    let mut world = World::new(width, height);
+    for x in 0..width {
+        world.spawn(ParticleNum::Iron, x, height - 1);
+    }
+    let mut rng = seeded_rng(1);
+    let middle = width / 2;
+    for _ in 0..60 {
+        for x in middle - 2..middle + 2 {
+            world.spawn(ParticleNum::Lava, x, 0);
+        }
+        world.step(&mut rng);
+    }
+    for _ in 0..100 {
+        world.step(&mut rng);
+    }
+    let count = |kind| world.particles().filter(|part| part.kind() == kind).count();
+    assert!(count(ParticleNum::Lava) > count(ParticleNum::Stone));
+    count_row(&world, height - 2, ParticleNum::Lava)
+}
+
+#[test]
+fn poured_lava_reaches_the_floor_as_lava() {
+    let on_floor = pour_lava(40, 15);
+    assert!(on_floor > 10, "apenas {} células de lava no chão", on_floor);
+    let on_floor = pour_lava(DEFAULT_WIDTH, DEFAULT_HEIGHT);
+    assert!(on_floor > 10, "apenas {} células de lava no chão", on_floor);
+}
+
+#[test]
+fn void_swallows_gas_agitated_and_fire() {
+    let mut world = World::new(9, 5);
+    for x in 0..9 {
+        world.spawn(ParticleNum::Iron, x, 0);
+        world.spawn(ParticleNum::Iron, x, 4);
+    }
+    for y in 1..4 {
+        world.spawn(ParticleNum::Void, 0, y);
+        world.spawn(ParticleNum::Void, 8, y);
+    }
+    for x in 1..8 {
+        world.spawn(ParticleNum::Smoke, x, 1);
+    }
+    for x in 2..7 {
+        world.spawn(ParticleNum::Agitated, x, 3);
+    }
+    run(&mut world, 0, 500);
+    let remaining = |world: &World| {
+        world
+            .particles()
+            .filter(|part| part.kind() != ParticleNum::Iron && part.kind() != ParticleNum::Void)
+            .count()
+    };
+    assert_eq!(remaining(&world), 0);
+
+    let mut world = World::new(3, 2);
+    for x in 0..3 {
+        world.spawn(ParticleNum::Void, x, 0);
+    }
+    world.spawn(ParticleNum::Fire, 1, 1);
+    run(&mut world, 0, 1);
+    assert!(world
+        .particles()
+        .all(|part| part.kind() == ParticleNum::Void));
+}
+
+#[test]
+fn swallowed_particles_do_not_overwrite_the_void() {
+    for seed in 0..2000 {
+        let mut world = World::new(3, 2);
+        for x in 0..3 {
+            world.spawn(ParticleNum::Iron, x, 0);
+        }
+        world.spawn(ParticleNum::Void, 0, 1);
+        world.spawn(ParticleNum::Void, 2, 1);
+        world.spawn(ParticleNum::Steam, 1, 1);
+        run(&mut world, seed, 1);
+        assert!(world.is_kind(0, 1, ParticleNum::Void), "semente {}", seed);
+        assert!(world.is_kind(2, 1, ParticleNum::Void), "semente {}", seed);
+    }
+}
+
+#[test]
+fn rejected_placements_are_counted() {
+    let mut world = World::new(4, 4);
+    assert!(world.spawn(ParticleNum::Iron, 1, 1));
+    assert!(!world.spawn(ParticleNum::Sand, 1, 1));
+    assert!(!world.spawn(ParticleNum::Sand, 4, 0));
+    assert_eq!(world.rejected_placements(), 2);
+    assert!(world.is_kind(1, 1, ParticleNum::Iron));
+    assert_eq!(world.particle_count(), 1);
+}
+
+#[test]
+fn index_math_does_not_overflow_u32() {
+    assert_eq!(position_to_index(0, 30_000, 40_000), 4_800_000_000);
+}
+
+/// O gerador da simulação é um algoritmo fixo: a mesma semente gera a mesma sequência em qualquer versão e plataforma
+#[test]
+fn seeded_rng_is_pinned() {
+    use rand::RngCore;
+    let mut rng = seeded_rng(42);
+    assert_eq!(rng.next_u64(), 12578764544318200737);
+}
+
+#[test]
+fn fast_sand_accelerates_and_stops_on_thin_iron() {
+    let mut world = World::new(3, 60);
+    for x in 0..3 {
+        world.spawn(ParticleNum::Iron, x, 50);
+    }
+    world.spawn(ParticleNum::Sand, 1, 0);
+    run(&mut world, 0, 10);
+    let fallen = (0..50)
+        .find(|&y| world.is_kind(1, y, ParticleNum::Sand))
+        .expect("a areia ainda está acima do ferro");
+    assert!(
+        fallen > 10,
+        "a areia caiu apenas {} células em 10 passos",
+        fallen
+    );
+    run(&mut world, 0, 50);
+    assert!(world.is_kind(1, 49, ParticleNum::Sand));
+    assert_eq!(world.particle_count(), 4);
+}
+
+/// Quantidade de partículas do material na linha y
+fn count_row(world: &World, y: u32, kind: ParticleNum) -> usize {
+    (0..world.width())
+        .filter(|&x| world.is_kind(x, y, kind))
+        .count()
+}
+
+#[test]
+fn sand_sinks_and_oil_floats_on_water() {
+    let mut world = World::new(6, 12);
+    for x in 0..6 {
+        world.spawn(ParticleNum::Sand, x, 0);
+        for y in 7..11 {
+            world.spawn(ParticleNum::Water, x, y);
+        }
+        world.spawn(ParticleNum::Oil, x, 11);
+    }
+    run(&mut world, 0, 400);
+    assert_eq!(count_row(&world, 11, ParticleNum::Sand), 6);
+    for y in 7..11 {
+        assert_eq!(count_row(&world, y, ParticleNum::Water), 6, "linha {}", y);
+    }
+    assert_eq!(count_row(&world, 6, ParticleNum::Oil), 6);
+}
+
+/// Areia sobre quatro linhas de água, em um mundo com a tabela de materiais informada
+fn sand_over_water(materials: Arc<MaterialTable>) -> World {
+    let mut world = World::with_materials(6, 6, materials);
+    for x in 0..6 {
+        world.spawn(ParticleNum::Sand, x, 0);
+        for y in 2..6 {
+            world.spawn(ParticleNum::Water, x, y);
+        }
+    }
+    world
+}
+
+#[test]
+fn worlds_use_their_own_material_tables() {
+    let light_sand = MaterialTable::from_toml(
+        r##"
+        [[material]]
+        name = "sand"
+        color = "#964b00"
+        density = 0.5
+        behaviour = "powder"
+        "##,
+    )
+    .unwrap();
+    let mut light = sand_over_water(Arc::new(light_sand));
+    let mut default = sand_over_water(default_materials());
+    run(&mut light, 0, 300);
+    run(&mut default, 0, 300);
+    assert_eq!(count_row(&light, 1, ParticleNum::Sand), 6);
+    assert_eq!(count_row(&default, 5, ParticleNum::Sand), 6);
+}
+
+#[test]
+fn charge_wave_moves_one_cell_per_step_and_decays() {
+    let mut world = World::new(20, 2);
+    for x in 0..20 {
+        world.spawn(ParticleNum::Iron, x, 1);
+    }
+    world.spawn(ParticleNum::Electricity, 0, 0);
+    let mut rng = seeded_rng(0);
+    for step in 1..=10 {
+        world.step(&mut rng);
+        let front = step - 1;
+        assert_eq!(world.charge(front, 1), CHARGE, "passo {}", step);
+        assert_eq!(world.charge(front + 1, 1), 0, "passo {}", step);
+        if front > 0 {
+            assert_eq!(world.charge(front - 1, 1), CHARGE - 1, "passo {}", step);
+        }
+    }
+    assert!(!world
+        .particles()
+        .any(|part| part.kind() == ParticleNum::Electricity));
+    run(&mut world, 0, 40);
+    assert!((0..20).all(|x| world.charge(x, 1) == 0));
+}
+
+#[test]
+fn wood_burns_into_ash() {
+    let mut world = World::new(8, 8);
+    for x in 0..8 {
+        world.spawn(ParticleNum::Stone, x, 7);
+    }
+    world.spawn(ParticleNum::Fire, 3, 5);
+    for x in 2..6 {
+        for y in 4..7 {
+            world.spawn(ParticleNum::Wood, x, y);
+        }
+    }
+    run(&mut world, 0, 2000);
+    let count = |kind| world.particles().filter(|part| part.kind() == kind).count();
+    let burned = 11 - count(ParticleNum::Wood);
+    assert!(burned > 1, "apenas {} madeiras queimaram", burned);
+    assert_eq!(count(ParticleNum::Fire), 0);
+    assert_eq!(count(ParticleNum::Ash), burned);
+}
+
+#[test]
+fn lava_falls_freely() {
+    let mut world = World::new(3, 60);
+    world.spawn(ParticleNum::Lava, 1, 0);
+    run(&mut world, 0, 30);
+    assert!(world.is_kind(1, 59, ParticleNum::Lava));
+}
+
+#[test]
+fn acid_is_used_up_and_leaves_glass_alone() {
+    let mut world = World::new(1, 10);
+    for y in 5..10 {
+        world.spawn(ParticleNum::Sand, 0, y);
+    }
+    world.spawn(ParticleNum::Acid, 0, 0);
+    run(&mut world, 0, 3000);
+    let count = |world: &World, kind| world.particles().filter(|part| part.kind() == kind).count();
+    assert_eq!(count(&world, ParticleNum::Acid), 0);
+    assert_eq!(count(&world, ParticleNum::Sand), 5 - ACID_STRENGTH as usize);
+
+    let mut world = World::new(3, 3);
+    for x in 0..3 {
+        world.spawn(ParticleNum::Glass, x, 2);
+    }
+    world.spawn(ParticleNum::Glass, 0, 1);
+    world.spawn(ParticleNum::Glass, 2, 1);
+    world.spawn(ParticleNum::Acid, 1, 1);
+    run(&mut world, 0, 3000);
+    assert_eq!(count(&world, ParticleNum::Glass), 5);
+    assert!(world.is_kind(1, 1, ParticleNum::Acid));
+}
+
+#[test]
+fn steam_condenses_as_it_cools() {
+    let mut world = World::new(20, 150);
+    for x in 8..12 {
+        world.spawn(ParticleNum::Steam, x, 140);
+    }
+    run(&mut world, 0, 30);
+    assert!(world
+        .particles()
+        .all(|part| part.kind() == ParticleNum::Steam));
+    assert!(!(0..20).any(|x| world.is_kind(x, 140, ParticleNum::Steam)));
+    run(&mut world, 0, 300);
+    assert!(world
+        .particles()
+        .all(|part| part.kind() == ParticleNum::Water));
+    assert_eq!(world.particle_count(), 4);
+}
+
+#[test]
+fn explosion_burns_the_core_and_throws_the_rest_up() {
+    let mut world = World::new(21, 21);
+    for x in 0..21 {
+        for y in 0..21 {
+            world.spawn(ParticleNum::Sand, x, y);
+        }
+    }
+    world.explode(10, 10, 6.0);
+    for (x, y) in [(10, 10), (13, 10), (10, 7), (8, 12)] {
+        assert!(world.is_kind(x, y, ParticleNum::Fire), "célula {},{}", x, y);
+    }
+    for (x, y) in [(15, 10), (5, 10), (10, 5)] {
+        let debris = world.get(x, y).unwrap();
+        assert_eq!(debris.kind(), ParticleNum::Sand);
+        assert!(debris.vy < 0.0, "célula {},{}", x, y);
+    }
+    assert!(world.get(15, 10).unwrap().vx > 0.0);
+    assert!(world.get(5, 10).unwrap().vx < 0.0);
+    assert_eq!(world.get(10, 0).unwrap().vy, 0.0);
+}
+
+#[test]
+fn gunpowder_explodes_next_to_fire() {
+    let mut world = World::new(9, 9);
+    for x in 0..9 {
+        world.spawn(ParticleNum::Stone, x, 8);
+    }
+    world.spawn(ParticleNum::Gunpowder, 4, 7);
+    world.spawn(ParticleNum::Fire, 5, 7);
+    run(&mut world, 0, 1);
+    assert!(!world
+        .particles()
+        .any(|part| part.kind() == ParticleNum::Gunpowder));
+    assert!(world.is_kind(4, 5, ParticleNum::Fire));
+}
+
+#[test]
+fn data_defined_explosive_explodes_next_to_fire() {
+    let table = MaterialTable::from_toml(
+        r##"
+        [[material]]
+        name = "tnt"
+        color = "#c03030"
+        density = 1.6
+        behaviour = "solid"
+        explosion_strength = 4.0
+        "##,
+    )
+    .unwrap();
+    let mut world = World::with_materials(9, 9, Arc::new(table));
+    let tnt = world.materials().find("tnt").unwrap();
+    world.spawn(tnt, 4, 4);
+    world.spawn(ParticleNum::Fire, 5, 4);
+    run(&mut world, 0, 1);
+    assert!(!world.particles().any(|part| part.kind() == tnt));
+    assert!(world.is_kind(4, 2, ParticleNum::Fire));
+}
+
+#[test]
+fn particles_stay_compact() {
+    assert_eq!(std::mem::size_of::<ParticleNum>(), 4);
+    assert!(std::mem::size_of::<Particle>() <= 20);
+}